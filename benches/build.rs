@@ -0,0 +1,49 @@
+//! Measures building a graph in which many edges share the same few constraints,
+//! each of which is compiled only once.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use ontime::formulae::{Expr, Formula};
+use ontime::temporal_graphs::TemporalGraphBuilder;
+
+const NODES: usize = 2000;
+
+fn t() -> Box<Expr> {
+    Box::new(Expr::Var("t".to_string()))
+}
+
+fn c(n: i64) -> Box<Expr> {
+    Box::new(Expr::Const(n))
+}
+
+// an always true bound, a window and a residue
+fn formulas() -> [Formula; 3] {
+    [
+        Formula::Ge(t(), c(0)),
+        Formula::And(vec![Formula::Ge(t(), c(5)), Formula::Le(t(), c(40))]),
+        Formula::Eq(Box::new(Expr::Mod(t(), 2)), c(0)),
+    ]
+}
+
+fn bench_build(c: &mut Criterion) {
+    let [always, window, even] = formulas();
+
+    c.bench_function("build, 3 shared formulas on 6000 edges", |b| {
+        b.iter(|| {
+            let mut builder = TemporalGraphBuilder::new().time_var("t");
+            for n in 0..NODES {
+                builder = builder.node(&format!("v{}", n), n % 2 == 0, None);
+            }
+            for n in 0..NODES {
+                let from = format!("v{}", n);
+                builder = builder
+                    .edge(&from, &format!("v{}", (n + 1) % NODES), always.clone())
+                    .edge(&from, &format!("v{}", (n * 7 + 3) % NODES), window.clone())
+                    .edge(&from, &from, even.clone());
+            }
+            builder.build().unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, bench_build);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+//! Compares solving with and without precomputed successor sets on a graph
+//! whose edges all carry nontrivial availability constraints.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use ontime::formulae::{Expr, Formula};
+use ontime::game::{reachable_at, reachable_at_cached};
+use ontime::temporal_graphs::{TemporalGraph, TemporalGraphBuilder};
+
+const NODES: usize = 200;
+const K: usize = 50;
+
+fn t() -> Box<Expr> {
+    Box::new(Expr::Var("t".to_string()))
+}
+
+fn c(n: i64) -> Box<Expr> {
+    Box::new(Expr::Const(n))
+}
+
+// every node has three successors, available at residues of t or within a window
+fn constraint_heavy_graph() -> TemporalGraph {
+    let mut builder = TemporalGraphBuilder::new().time_var("t");
+    for n in 0..NODES {
+        builder = builder.node(&format!("v{}", n), n % 2 == 0, None);
+    }
+    for n in 0..NODES {
+        let from = format!("v{}", n);
+        let window = Formula::And(vec![
+            Formula::Ge(t(), c((n % 10) as i64)),
+            Formula::Le(t(), c(40)),
+        ]);
+        let residue = |m: i64| Formula::Eq(Box::new(Expr::Mod(t(), m)), c(n as i64 % m));
+        builder = builder
+            .edge(
+                &from,
+                &format!("v{}", (n + 1) % NODES),
+                Formula::Or(vec![residue(3), window]),
+            )
+            .edge(&from, &format!("v{}", (n * 7 + 3) % NODES), residue(5))
+            .edge(&from, &from, Formula::Not(Box::new(residue(4))));
+    }
+    builder.build().unwrap()
+}
+
+fn targets() -> Vec<Vec<bool>> {
+    (0..10)
+        .map(|t| (0..NODES).map(|n| n % 10 == t).collect())
+        .collect()
+}
+
+fn bench_successors(c: &mut Criterion) {
+    let graph = constraint_heavy_graph();
+    let targets = targets();
+
+    c.bench_function("reachable_at, 10 targets", |b| {
+        b.iter(|| {
+            for target in &targets {
+                reachable_at(&graph, K, true, target);
+            }
+        })
+    });
+
+    c.bench_function("reachable_at_cached, 10 targets", |b| {
+        b.iter(|| {
+            let successors = graph.precompute_successors(K);
+            for target in &targets {
+                reachable_at_cached(&graph, K, true, target, &successors);
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_successors);
+criterion_main!(benches);
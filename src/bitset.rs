@@ -0,0 +1,86 @@
+//! Set operations on node sets in the representation used throughout the crate:
+//! a `&[bool]` of length `node_count`, as returned by [`crate::game::reachable_at`].
+//!
+//! Binary operations fail if the two sets have different lengths.
+
+// applies `op` element-wise, checking that the lengths agree
+fn zip_with(a: &[bool], b: &[bool], op: impl Fn(bool, bool) -> bool) -> Result<Vec<bool>, String> {
+    if a.len() != b.len() {
+        return Err(format!(
+            "node sets have different lengths: {} and {}",
+            a.len(),
+            b.len()
+        ));
+    }
+    Ok(a.iter().zip(b).map(|(&x, &y)| op(x, y)).collect())
+}
+
+/// Intersection: the nodes in both `a` and `b`.
+pub fn and(a: &[bool], b: &[bool]) -> Result<Vec<bool>, String> {
+    zip_with(a, b, |x, y| x && y)
+}
+
+/// Union: the nodes in `a` or `b`.
+pub fn or(a: &[bool], b: &[bool]) -> Result<Vec<bool>, String> {
+    zip_with(a, b, |x, y| x || y)
+}
+
+/// Complement: the nodes not in `a`.
+pub fn not(a: &[bool]) -> Vec<bool> {
+    a.iter().map(|&x| !x).collect()
+}
+
+/// Difference: the nodes in `a` but not in `b`.
+pub fn diff(a: &[bool], b: &[bool]) -> Result<Vec<bool>, String> {
+    zip_with(a, b, |x, y| x && !y)
+}
+
+/// The number of nodes in `a`, see also [`crate::game::winning_count`].
+pub fn count(a: &[bool]) -> usize {
+    a.iter().filter(|&&x| x).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: [bool; 4] = [true, true, false, false];
+    const B: [bool; 4] = [true, false, true, false];
+
+    #[test]
+    fn test_and() {
+        assert_eq!(and(&A, &B), Ok(vec![true, false, false, false]));
+    }
+
+    #[test]
+    fn test_or() {
+        assert_eq!(or(&A, &B), Ok(vec![true, true, true, false]));
+    }
+
+    #[test]
+    fn test_not() {
+        assert_eq!(not(&A), [false, false, true, true]);
+        assert_eq!(not(&[]), []);
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(diff(&A, &B), Ok(vec![false, true, false, false]));
+        assert_eq!(diff(&B, &A), Ok(vec![false, false, true, false]));
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(count(&A), 2);
+        assert_eq!(count(&and(&A, &B).unwrap()), 1);
+        assert_eq!(count(&[]), 0);
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        let err = "node sets have different lengths: 4 and 3".to_string();
+        assert_eq!(and(&A, &B[..3]), Err(err.clone()));
+        assert_eq!(or(&A, &B[..3]), Err(err.clone()));
+        assert_eq!(diff(&A, &B[..3]), Err(err));
+    }
+}
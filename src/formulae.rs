@@ -1,21 +1,33 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Expr {
     Add(Box<Expr>, Box<Expr>),
     Sub(Box<Expr>, Box<Expr>),
     MulConst(i64, Box<Expr>),
     Mod(Box<Expr>, i64),
+    Min(Box<Expr>, Box<Expr>),
+    Max(Box<Expr>, Box<Expr>),
+    /// `(ite cond e1 e2)`: `e1` if the condition holds, `e2` otherwise
+    Ite(Box<Formula>, Box<Expr>, Box<Expr>),
     Var(String),
     Const(i64),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Formula {
     Forall(String, Box<Formula>),
     Exists(String, Box<Formula>),
+    /// `(forall v in lo hi body)`: the body holds for every integer `lo <= v <= hi`
+    ForallIn(String, i64, i64, Box<Formula>),
+    /// `(exists v in lo hi body)`: the body holds for some integer `lo <= v <= hi`
+    ExistsIn(String, i64, i64, Box<Formula>),
     And(Vec<Formula>),
     Or(Vec<Formula>),
+    /// Exclusive or: true if an odd number of the children hold.
+    /// So `Xor` of a single formula is that formula, and of no formulas is false.
+    Xor(Vec<Formula>),
     Not(Box<Formula>),
     Eq(Box<Expr>, Box<Expr>),
     Neq(Box<Expr>, Box<Expr>),
@@ -29,16 +41,24 @@ pub enum Formula {
 
 impl Formula {
     /// Attempts to turn the formula into a closure `Fn(usize) -> bool`.
-    /// Only works if the formula is quantifier-free and has at most one free variable.
+    /// Only works if the formula is quantifier-free, apart from quantifiers over an
+    /// explicit range (`ForallIn` and `ExistsIn`, which are expanded),
+    /// and has at most one free variable, and no `mod` by zero.
     /// The closure does not borrow from the formula and is `'static`.
+    /// Arithmetic saturates at the bounds of `i64` instead of wrapping around,
+    /// see [`Formula::try_eval`] for an evaluation that reports overflow instead.
     pub fn as_closure(self) -> Result<Box<dyn Fn(usize) -> bool + 'static>, &'static str> {
-        if !self.is_quantifier_free() {
+        let formula = self.expand_ranged_quantifiers();
+        if !formula.is_quantifier_free() {
             return Err("Formula contains quantifiers");
         }
-        let free_vars = self.free_variables();
+        let free_vars = formula.free_variables();
         if free_vars.len() > 1 {
             return Err("Formula must have at most one free variable");
         }
+        if formula.has_modulo_by_zero() {
+            return Err("Formula contains a modulo by zero");
+        }
         let var_opt = free_vars.into_iter().next().map(|s| s.to_string());
 
         fn expr_to_closure(
@@ -49,25 +69,42 @@ impl Formula {
                 crate::formulae::Expr::Add(e1, e2) => {
                     let c1 = expr_to_closure(*e1, var.clone());
                     let c2 = expr_to_closure(*e2, var.clone());
-                    Box::new(move |x| c1(x) + c2(x))
+                    Box::new(move |x| c1(x).saturating_add(c2(x)))
                 }
                 crate::formulae::Expr::Sub(e1, e2) => {
                     let c1 = expr_to_closure(*e1, var.clone());
                     let c2 = expr_to_closure(*e2, var.clone());
-                    Box::new(move |x| c1(x) - c2(x))
+                    Box::new(move |x| c1(x).saturating_sub(c2(x)))
                 }
                 crate::formulae::Expr::MulConst(c, e) => {
                     let ce = expr_to_closure(*e, var.clone());
-                    Box::new(move |x| c * ce(x))
+                    Box::new(move |x| c.saturating_mul(ce(x)))
                 }
                 crate::formulae::Expr::Mod(e, m) => {
                     let ce = expr_to_closure(*e, var.clone());
-                    Box::new(move |x| ce(x) % m)
+                    // `wrapping_rem` only differs for `i64::MIN % -1`, which is 0
+                    Box::new(move |x| ce(x).wrapping_rem(m))
+                }
+                crate::formulae::Expr::Min(e1, e2) => {
+                    let c1 = expr_to_closure(*e1, var.clone());
+                    let c2 = expr_to_closure(*e2, var.clone());
+                    Box::new(move |x| c1(x).min(c2(x)))
+                }
+                crate::formulae::Expr::Max(e1, e2) => {
+                    let c1 = expr_to_closure(*e1, var.clone());
+                    let c2 = expr_to_closure(*e2, var.clone());
+                    Box::new(move |x| c1(x).max(c2(x)))
+                }
+                crate::formulae::Expr::Ite(cond, e1, e2) => {
+                    let cc = formula_to_closure(*cond, var.clone());
+                    let c1 = expr_to_closure(*e1, var.clone());
+                    let c2 = expr_to_closure(*e2, var.clone());
+                    Box::new(move |x| if cc(x) { c1(x) } else { c2(x) })
                 }
                 crate::formulae::Expr::Var(v) => {
                     if let Some(ref var_name) = var {
                         if v == *var_name {
-                            Box::new(move |x| x as i64)
+                            Box::new(move |x| i64::try_from(x).unwrap_or(i64::MAX))
                         } else {
                             // Should not happen for quantifier-free, single-variable formulas
                             Box::new(|_| 0)
@@ -100,6 +137,13 @@ impl Formula {
                         .collect();
                     Box::new(move |x| cs.iter().any(|c| c(x)))
                 }
+                Formula::Xor(fs) => {
+                    let cs: Vec<_> = fs
+                        .into_iter()
+                        .map(|f| formula_to_closure(f, var.clone()))
+                        .collect();
+                    Box::new(move |x| cs.iter().filter(|c| c(x)).count() % 2 == 1)
+                }
                 Formula::Not(f) => {
                     let c = formula_to_closure(*f, var);
                     Box::new(move |x| !c(x))
@@ -140,24 +184,197 @@ impl Formula {
             }
         }
 
-        let closure = formula_to_closure(self, var_opt);
+        let closure = formula_to_closure(formula, var_opt);
         Ok(closure)
     }
 
-    /// Returns true if the formula contains no quantifiers (Forall or Exists).
+    /// Like [`Formula::as_closure`], but quantifiers are allowed over variables
+    /// with a bounded domain: `forall` and `exists` are expanded into a conjunction
+    /// or disjunction over all values of the variable in `domains`.
+    /// Fails if a quantified variable has no domain.
+    pub fn as_closure_bounded(
+        self,
+        domains: HashMap<String, RangeInclusive<i64>>,
+    ) -> Result<Box<dyn Fn(usize) -> bool + 'static>, String> {
+        self.expand_quantifiers(&domains)?
+            .as_closure()
+            .map_err(String::from)
+    }
+
+    // replaces quantifiers by conjunctions/disjunctions over the domain of their variable
+    fn expand_quantifiers(
+        self,
+        domains: &HashMap<String, RangeInclusive<i64>>,
+    ) -> Result<Formula, String> {
+        let expand = |v: String, body: Box<Formula>| {
+            let domain = domains
+                .get(&v)
+                .ok_or_else(|| format!("quantified variable {} has no domain", v))?;
+            let body = body.expand_quantifiers(domains)?;
+            Ok::<_, String>(body.instances(&v, domain.clone()))
+        };
+        let expand_all = |fs: Vec<Formula>| {
+            fs.into_iter()
+                .map(|f| f.expand_quantifiers(domains))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match self {
+            Formula::Forall(v, body) => Formula::And(expand(v, body)?),
+            Formula::Exists(v, body) => Formula::Or(expand(v, body)?),
+            Formula::ForallIn(v, lo, hi, body) => {
+                Formula::And(body.expand_quantifiers(domains)?.instances(&v, lo..=hi))
+            }
+            Formula::ExistsIn(v, lo, hi, body) => {
+                Formula::Or(body.expand_quantifiers(domains)?.instances(&v, lo..=hi))
+            }
+            Formula::And(fs) => Formula::And(expand_all(fs)?),
+            Formula::Or(fs) => Formula::Or(expand_all(fs)?),
+            Formula::Xor(fs) => Formula::Xor(expand_all(fs)?),
+            Formula::Not(f) => Formula::Not(Box::new(f.expand_quantifiers(domains)?)),
+            // quantifiers in `ite` conditions are not expanded, and rejected by `as_closure`
+            f => f,
+        })
+    }
+
+    // replaces quantifiers over an explicit range by conjunctions/disjunctions over it
+    fn expand_ranged_quantifiers(self) -> Formula {
+        let expand_all = |fs: Vec<Formula>| {
+            fs.into_iter()
+                .map(Formula::expand_ranged_quantifiers)
+                .collect()
+        };
+        match self {
+            Formula::ForallIn(v, lo, hi, body) => {
+                Formula::And(body.expand_ranged_quantifiers().instances(&v, lo..=hi))
+            }
+            Formula::ExistsIn(v, lo, hi, body) => {
+                Formula::Or(body.expand_ranged_quantifiers().instances(&v, lo..=hi))
+            }
+            Formula::Forall(v, body) => {
+                Formula::Forall(v, Box::new(body.expand_ranged_quantifiers()))
+            }
+            Formula::Exists(v, body) => {
+                Formula::Exists(v, Box::new(body.expand_ranged_quantifiers()))
+            }
+            Formula::And(fs) => Formula::And(expand_all(fs)),
+            Formula::Or(fs) => Formula::Or(expand_all(fs)),
+            Formula::Xor(fs) => Formula::Xor(expand_all(fs)),
+            Formula::Not(f) => Formula::Not(Box::new(f.expand_ranged_quantifiers())),
+            // as in `expand_quantifiers`, `ite` conditions are left alone
+            f => f,
+        }
+    }
+
+    // the formula with `var` replaced by each value of `domain` in turn
+    fn instances(&self, var: &str, domain: RangeInclusive<i64>) -> Vec<Formula> {
+        domain
+            .map(|c| self.clone().substitute(var, &Expr::Const(c)))
+            .collect()
+    }
+
+    /// Returns the value of a formula without free variables, such as `(>= 5 3)`.
+    ///
+    /// Returns `None` if the formula has a free variable or cannot be evaluated,
+    /// see [`Formula::try_eval`].
+    pub fn constant_value(&self) -> Option<bool> {
+        if !self.free_variables().is_empty() {
+            return None;
+        }
+        self.try_eval(&HashMap::new()).ok()
+    }
+
+    /// Replaces a formula with a [constant value](Formula::constant_value)
+    /// by `Formula::True` or `Formula::False`, leaving any other formula unchanged.
+    pub fn fold_constant(self) -> Formula {
+        match self.constant_value() {
+            Some(true) => Formula::True,
+            Some(false) => Formula::False,
+            None => self,
+        }
+    }
+
+    /// Evaluates the formula with the variable values in `env`.
+    ///
+    /// Unlike [`Formula::as_closure`] this handles any number of variables, and fails
+    /// instead of saturating if the arithmetic overflows `i64`. It also fails for a
+    /// `mod` by zero, a variable without a value, and for `Forall` and `Exists`,
+    /// which have no range to evaluate over.
+    pub fn try_eval(&self, env: &HashMap<String, i64>) -> Result<bool, String> {
+        let all = |fs: &[Formula]| {
+            fs.iter()
+                .map(|f| f.try_eval(env))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let ranged = |v: &str, lo: i64, hi: i64, body: &Formula| {
+            let mut env = env.clone();
+            (lo..=hi)
+                .map(|c| {
+                    env.insert(v.to_string(), c);
+                    body.try_eval(&env)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match self {
+            Formula::Forall(v, _) | Formula::Exists(v, _) => {
+                return Err(format!("quantified variable {} has no range", v));
+            }
+            Formula::ForallIn(v, lo, hi, body) => ranged(v, *lo, *hi, body)?.into_iter().all(|b| b),
+            Formula::ExistsIn(v, lo, hi, body) => ranged(v, *lo, *hi, body)?.into_iter().any(|b| b),
+            Formula::And(fs) => all(fs)?.into_iter().all(|b| b),
+            Formula::Or(fs) => all(fs)?.into_iter().any(|b| b),
+            Formula::Xor(fs) => all(fs)?.into_iter().filter(|&b| b).count() % 2 == 1,
+            Formula::Not(f) => !f.try_eval(env)?,
+            Formula::Eq(e1, e2) => e1.try_eval(env)? == e2.try_eval(env)?,
+            Formula::Neq(e1, e2) => e1.try_eval(env)? != e2.try_eval(env)?,
+            Formula::Lt(e1, e2) => e1.try_eval(env)? < e2.try_eval(env)?,
+            Formula::Le(e1, e2) => e1.try_eval(env)? <= e2.try_eval(env)?,
+            Formula::Gt(e1, e2) => e1.try_eval(env)? > e2.try_eval(env)?,
+            Formula::Ge(e1, e2) => e1.try_eval(env)? >= e2.try_eval(env)?,
+            Formula::True => true,
+            Formula::False => false,
+        })
+    }
+
+    /// Returns true if the formula contains no quantifiers (Forall or Exists,
+    /// also over a range), including in the conditions of `ite` expressions.
     pub fn is_quantifier_free(&self) -> bool {
         match self {
-            Formula::Forall(_, _) | Formula::Exists(_, _) => false,
-            Formula::And(fs) | Formula::Or(fs) => fs.iter().all(|f| f.is_quantifier_free()),
+            Formula::Forall(_, _)
+            | Formula::Exists(_, _)
+            | Formula::ForallIn(..)
+            | Formula::ExistsIn(..) => false,
+            Formula::And(fs) | Formula::Or(fs) | Formula::Xor(fs) => {
+                fs.iter().all(|f| f.is_quantifier_free())
+            }
             Formula::Not(f) => f.is_quantifier_free(),
-            Formula::Eq(_, _)
-            | Formula::Neq(_, _)
-            | Formula::Lt(_, _)
-            | Formula::Le(_, _)
-            | Formula::Gt(_, _)
-            | Formula::Ge(_, _)
-            | Formula::True
-            | Formula::False => true,
+            Formula::Eq(e1, e2)
+            | Formula::Neq(e1, e2)
+            | Formula::Lt(e1, e2)
+            | Formula::Le(e1, e2)
+            | Formula::Gt(e1, e2)
+            | Formula::Ge(e1, e2) => e1.is_quantifier_free() && e2.is_quantifier_free(),
+            Formula::True | Formula::False => true,
+        }
+    }
+
+    // Returns true if the formula contains `(mod e 0)`.
+    fn has_modulo_by_zero(&self) -> bool {
+        match self {
+            Formula::Forall(_, f)
+            | Formula::Exists(_, f)
+            | Formula::ForallIn(_, _, _, f)
+            | Formula::ExistsIn(_, _, _, f)
+            | Formula::Not(f) => f.has_modulo_by_zero(),
+            Formula::And(fs) | Formula::Or(fs) | Formula::Xor(fs) => {
+                fs.iter().any(|f| f.has_modulo_by_zero())
+            }
+            Formula::Eq(e1, e2)
+            | Formula::Neq(e1, e2)
+            | Formula::Lt(e1, e2)
+            | Formula::Le(e1, e2)
+            | Formula::Gt(e1, e2)
+            | Formula::Ge(e1, e2) => e1.has_modulo_by_zero() || e2.has_modulo_by_zero(),
+            Formula::True | Formula::False => false,
         }
     }
 
@@ -167,6 +384,369 @@ impl Formula {
         free.len() == 1 && free.contains(t)
     }
 
+    /// Returns true if the formula holds for every value `0..=k` of `var`.
+    ///
+    /// Only decided for quantifier-free formulas without free variables other than `var`;
+    /// for any other formula this returns false.
+    pub fn is_tautology_over(&self, var: &str, k: usize) -> bool {
+        self.values_in(var, 0..=k)
+            .is_some_and(|mut values| values.all(|b| b))
+    }
+
+    /// Returns true if the formula holds for no value `0..=k` of `var`.
+    ///
+    /// Only decided for quantifier-free formulas without free variables other than `var`;
+    /// for any other formula this returns false.
+    pub fn is_contradiction_over(&self, var: &str, k: usize) -> bool {
+        self.values_in(var, 0..=k)
+            .is_some_and(|mut values| values.all(|b| !b))
+    }
+
+    /// Returns true if the formula holds for some value of `var` in `range`.
+    ///
+    /// Only decided for quantifier-free formulas without free variables other than `var`;
+    /// for any other formula this returns false.
+    pub fn is_satisfiable_in(&self, var: &str, range: RangeInclusive<usize>) -> bool {
+        self.values_in(var, range)
+            .is_some_and(|mut values| values.any(|b| b))
+    }
+
+    /// Returns true if both formulas have the same truth value for every value `0..=k` of `var`.
+    ///
+    /// This is a decision over the finite range only, not general logical equivalence:
+    /// `(>= t 5)` and `(> t 5)` compare equal for `k < 5`.
+    /// Formulas that cannot be evaluated (quantifiers, or other free variables)
+    /// compare equal only if they are structurally equal.
+    pub fn logically_eq(&self, other: &Formula, var: &str, k: usize) -> bool {
+        if self == other {
+            return true;
+        }
+        match (self.values_in(var, 0..=k), other.values_in(var, 0..=k)) {
+            (Some(a), Some(b)) => a.eq(b),
+            _ => false,
+        }
+    }
+
+    /// Recognises formulas that hold for an interval of values of `var`: comparisons
+    /// (`=`, `<`, `<=`, `>`, `>=`) between `var` and a constant, `True`,
+    /// and conjunctions of these.
+    ///
+    /// Returns the tightest `(lo, hi)` such that the formula holds for `var = t`
+    /// exactly if `lo <= t <= hi`, where `None` means unbounded (a lower bound of at
+    /// most 0 is `None`). An unsatisfiable conjunction gives the empty interval
+    /// `(Some(1), Some(0))`. Returns `None` if the formula has any other shape.
+    pub fn as_interval(&self, var: &str) -> Option<(Option<usize>, Option<usize>)> {
+        let mut lo: i64 = 0;
+        let mut hi: Option<i64> = None;
+        self.collect_bounds(var, &mut lo, &mut hi)?;
+        match hi {
+            Some(hi) if hi < lo => Some((Some(1), Some(0))),
+            _ => Some(((lo > 0).then_some(lo as usize), hi.map(|hi| hi as usize))),
+        }
+    }
+
+    /// Returns the values of `var` in `0..=k` for which the formula holds, in increasing order.
+    ///
+    /// Uses [`Formula::as_interval`] where possible and falls back to evaluating the formula
+    /// otherwise, e.g. if `var` occurs on both sides of a comparison.
+    /// Returns `None` if the formula cannot be evaluated in terms of `var` alone.
+    pub fn solution_set_up_to(&self, var: &str, k: usize) -> Option<Vec<usize>> {
+        if let Some((lo, hi)) = self.as_interval(var) {
+            let hi = hi.map_or(k, |hi| hi.min(k));
+            return Some((lo.unwrap_or(0)..=hi).collect());
+        }
+        let values = self.values_in(var, 0..=k)?;
+        Some(
+            values
+                .zip(0..)
+                .filter(|(v, _)| *v)
+                .map(|(_, t)| t)
+                .collect(),
+        )
+    }
+
+    /// Recognises formulas that hold for a union of arithmetic progressions in `var`:
+    /// atoms `(= (mod var m) r)` (either way round), and disjunctions of these.
+    ///
+    /// Returns the `(modulus, residue)` pairs, in order, such that the formula holds
+    /// for `var = t >= 0` exactly if `t % modulus == residue` for one of them.
+    /// Atoms whose residue is out of range for their modulus never hold and are left
+    /// out, so an empty list means the formula never holds.
+    /// Returns `None` if the formula has any other shape, or a modulus is 0.
+    pub fn as_ap_union(&self, var: &str) -> Option<Vec<(usize, usize)>> {
+        let mut aps = Vec::new();
+        self.collect_aps(var, &mut aps)?;
+        Some(aps)
+    }
+
+    // appends the progressions of an AP-union formula, see `as_ap_union`
+    fn collect_aps(&self, var: &str, aps: &mut Vec<(usize, usize)>) -> Option<()> {
+        let (e1, e2) = match self {
+            Formula::Or(fs) => return fs.iter().try_for_each(|f| f.collect_aps(var, aps)),
+            Formula::Eq(e1, e2) => (e1.as_ref(), e2.as_ref()),
+            _ => return None,
+        };
+        let (m, r) = match (e1, e2) {
+            (Expr::Mod(e, m), Expr::Const(r)) | (Expr::Const(r), Expr::Mod(e, m)) if matches!(e.as_ref(), Expr::Var(v) if v == var) => {
+                (*m, *r)
+            }
+            _ => return None,
+        };
+        // for `var >= 0`, `mod` is never negative, whatever the sign of `m`
+        let m = usize::try_from(m.unsigned_abs()).ok().filter(|&m| m > 0)?;
+        if let Ok(r) = usize::try_from(r)
+            && r < m
+        {
+            aps.push((m, r));
+        }
+        Some(())
+    }
+
+    // narrows `lo..=hi` by the bounds of an interval-shaped formula, see `as_interval`
+    fn collect_bounds(&self, var: &str, lo: &mut i64, hi: &mut Option<i64>) -> Option<()> {
+        let (e1, e2) = match self {
+            Formula::True => return Some(()),
+            Formula::And(fs) => {
+                return fs.iter().try_for_each(|f| f.collect_bounds(var, lo, hi));
+            }
+            Formula::Eq(e1, e2)
+            | Formula::Lt(e1, e2)
+            | Formula::Le(e1, e2)
+            | Formula::Gt(e1, e2)
+            | Formula::Ge(e1, e2) => (e1.as_ref(), e2.as_ref()),
+            _ => return None,
+        };
+        // `c op var` is mirrored into `var op' c`
+        let (c, mirrored) = match (e1, e2) {
+            (Expr::Var(v), Expr::Const(c)) if v == var => (*c, false),
+            (Expr::Const(c), Expr::Var(v)) if v == var => (*c, true),
+            _ => return None,
+        };
+        let (lower, upper) = match (self, mirrored) {
+            (Formula::Eq(..), _) => (Some(c), Some(c)),
+            (Formula::Lt(..), false) | (Formula::Gt(..), true) => (None, Some(c.saturating_sub(1))),
+            (Formula::Le(..), false) | (Formula::Ge(..), true) => (None, Some(c)),
+            (Formula::Gt(..), false) | (Formula::Lt(..), true) => (Some(c.saturating_add(1)), None),
+            (Formula::Ge(..), false) | (Formula::Le(..), true) => (Some(c), None),
+            _ => unreachable!("only comparisons reach here"),
+        };
+        if let Some(lower) = lower {
+            *lo = (*lo).max(lower);
+        }
+        if let Some(upper) = upper {
+            *hi = Some(hi.map_or(upper, |hi| hi.min(upper)));
+        }
+        Some(())
+    }
+
+    // truth values of the formula for `var` in `range`, if it can be evaluated
+    fn values_in(
+        &self,
+        var: &str,
+        range: RangeInclusive<usize>,
+    ) -> Option<impl Iterator<Item = bool>> {
+        if self.free_variables().iter().any(|v| *v != var) {
+            return None;
+        }
+        let closure = self.clone().as_closure().ok()?;
+        Some(range.map(closure))
+    }
+
+    /// Returns the period of the formula's truth value in its time variable,
+    /// if it is periodic.
+    ///
+    /// A formula is periodic if every occurrence of a variable sits below a `mod`
+    /// whose argument is affine (built from `+`, `-`, `*`, variables and constants)
+    /// and does not change sign for times `t >= 0`.
+    /// The period is the least common multiple of the moduli involved;
+    /// formulas without variables have period 1.
+    /// Every free variable is treated as the time variable, as in `as_closure`.
+    /// Since `mod` truncates towards zero, an argument such as `(- t 5)` has a
+    /// different residue pattern below `t = 5`, so such formulas are not periodic.
+    pub fn period(&self) -> Option<usize> {
+        match self {
+            Formula::Forall(_, _)
+            | Formula::Exists(_, _)
+            | Formula::ForallIn(..)
+            | Formula::ExistsIn(..) => None,
+            Formula::And(fs) | Formula::Or(fs) | Formula::Xor(fs) => fs
+                .iter()
+                .try_fold(1, |acc, f| f.period().map(|p| lcm(acc, p))),
+            Formula::Not(f) => f.period(),
+            Formula::Eq(e1, e2)
+            | Formula::Neq(e1, e2)
+            | Formula::Lt(e1, e2)
+            | Formula::Le(e1, e2)
+            | Formula::Gt(e1, e2)
+            | Formula::Ge(e1, e2) => Some(lcm(e1.period()?, e2.period()?)),
+            Formula::True | Formula::False => Some(1),
+        }
+    }
+
+    /// Returns an equivalent formula with trivial structure removed: nested `and`s
+    /// and `or`s are flattened, `True` and `False` children are absorbed, `and`, `or`
+    /// and `xor` of a single child are unwrapped, double negations are dropped, and
+    /// comparisons between constants are evaluated. Quantifier bodies are simplified too.
+    pub fn simplify(self) -> Formula {
+        // `and` (`absorbing` = `False`) or `or` (`absorbing` = `True`)
+        let flatten = |fs: Vec<Formula>, absorbing: Formula| {
+            let mut children = Vec::new();
+            for f in fs.into_iter().map(Formula::simplify) {
+                match f {
+                    f if f == absorbing => return absorbing,
+                    Formula::True | Formula::False => {}
+                    Formula::And(gs) if absorbing == Formula::False => children.extend(gs),
+                    Formula::Or(gs) if absorbing == Formula::True => children.extend(gs),
+                    f => children.push(f),
+                }
+            }
+            match (children.len(), absorbing) {
+                (0, Formula::False) => Formula::True,
+                (0, _) => Formula::False,
+                (1, _) => children.pop().unwrap(),
+                (_, Formula::False) => Formula::And(children),
+                (_, _) => Formula::Or(children),
+            }
+        };
+        let compare = |e1: Box<Expr>, e2: Box<Expr>, op: fn(Box<Expr>, Box<Expr>) -> Formula| match (
+            e1.as_ref(),
+            e2.as_ref(),
+        ) {
+            (Expr::Const(_), Expr::Const(_)) => match op(e1, e2).try_eval(&HashMap::new()) {
+                Ok(true) => Formula::True,
+                Ok(false) => Formula::False,
+                Err(_) => unreachable!("constants always evaluate"),
+            },
+            _ => op(e1, e2),
+        };
+        match self {
+            Formula::And(fs) => flatten(fs, Formula::False),
+            Formula::Or(fs) => flatten(fs, Formula::True),
+            Formula::Xor(fs) => {
+                // a `True` child flips the result, a `False` child does not matter
+                let mut flipped = false;
+                let mut children = Vec::new();
+                for f in fs.into_iter().map(Formula::simplify) {
+                    match f {
+                        Formula::True => flipped = !flipped,
+                        Formula::False => {}
+                        f => children.push(f),
+                    }
+                }
+                let f = match children.len() {
+                    0 => Formula::False,
+                    1 => children.pop().unwrap(),
+                    _ => Formula::Xor(children),
+                };
+                if flipped { f.negate() } else { f }
+            }
+            Formula::Not(f) => f.simplify().negate(),
+            Formula::Eq(e1, e2) => compare(e1, e2, Formula::Eq),
+            Formula::Neq(e1, e2) => compare(e1, e2, Formula::Neq),
+            Formula::Lt(e1, e2) => compare(e1, e2, Formula::Lt),
+            Formula::Le(e1, e2) => compare(e1, e2, Formula::Le),
+            Formula::Gt(e1, e2) => compare(e1, e2, Formula::Gt),
+            Formula::Ge(e1, e2) => compare(e1, e2, Formula::Ge),
+            Formula::Forall(v, body) => Formula::Forall(v, Box::new(body.simplify())),
+            Formula::Exists(v, body) => Formula::Exists(v, Box::new(body.simplify())),
+            Formula::ForallIn(v, lo, hi, body) => {
+                Formula::ForallIn(v, lo, hi, Box::new(body.simplify()))
+            }
+            Formula::ExistsIn(v, lo, hi, body) => {
+                Formula::ExistsIn(v, lo, hi, Box::new(body.simplify()))
+            }
+            Formula::True => Formula::True,
+            Formula::False => Formula::False,
+        }
+    }
+
+    // the negation of an already simplified formula, itself simplified
+    fn negate(self) -> Formula {
+        match self {
+            Formula::True => Formula::False,
+            Formula::False => Formula::True,
+            Formula::Not(f) => *f,
+            f => Formula::Not(Box::new(f)),
+        }
+    }
+
+    /// Replaces every free occurrence of `var` by `replacement`.
+    ///
+    /// Bound occurrences under a quantifier on `var` are left untouched.
+    /// Quantified variables that occur free in `replacement` are renamed first,
+    /// so that the replacement is not captured.
+    pub fn substitute(self, var: &str, replacement: &Expr) -> Formula {
+        let sub = |f: Formula| f.substitute(var, replacement);
+        let sub_expr = |e: Box<Expr>| Box::new(e.substitute(var, replacement));
+        match self {
+            Formula::Forall(v, body) => {
+                Self::substitute_quantified(Formula::Forall, v, *body, var, replacement)
+            }
+            Formula::Exists(v, body) => {
+                Self::substitute_quantified(Formula::Exists, v, *body, var, replacement)
+            }
+            Formula::ForallIn(v, lo, hi, body) => Self::substitute_quantified(
+                |v, body| Formula::ForallIn(v, lo, hi, body),
+                v,
+                *body,
+                var,
+                replacement,
+            ),
+            Formula::ExistsIn(v, lo, hi, body) => Self::substitute_quantified(
+                |v, body| Formula::ExistsIn(v, lo, hi, body),
+                v,
+                *body,
+                var,
+                replacement,
+            ),
+            Formula::And(fs) => Formula::And(fs.into_iter().map(sub).collect()),
+            Formula::Or(fs) => Formula::Or(fs.into_iter().map(sub).collect()),
+            Formula::Xor(fs) => Formula::Xor(fs.into_iter().map(sub).collect()),
+            Formula::Not(f) => Formula::Not(Box::new(sub(*f))),
+            Formula::Eq(e1, e2) => Formula::Eq(sub_expr(e1), sub_expr(e2)),
+            Formula::Neq(e1, e2) => Formula::Neq(sub_expr(e1), sub_expr(e2)),
+            Formula::Lt(e1, e2) => Formula::Lt(sub_expr(e1), sub_expr(e2)),
+            Formula::Le(e1, e2) => Formula::Le(sub_expr(e1), sub_expr(e2)),
+            Formula::Gt(e1, e2) => Formula::Gt(sub_expr(e1), sub_expr(e2)),
+            Formula::Ge(e1, e2) => Formula::Ge(sub_expr(e1), sub_expr(e2)),
+            Formula::True => Formula::True,
+            Formula::False => Formula::False,
+        }
+    }
+
+    // substitution below a quantifier on `v`, renaming `v` if the replacement would be captured
+    fn substitute_quantified(
+        quantifier: impl Fn(String, Box<Formula>) -> Formula,
+        v: String,
+        body: Formula,
+        var: &str,
+        replacement: &Expr,
+    ) -> Formula {
+        if v == var {
+            return quantifier(v, Box::new(body));
+        }
+        let replacement_vars = replacement.free_variables();
+        if !replacement_vars.contains(v.as_str()) {
+            return quantifier(v, Box::new(body.substitute(var, replacement)));
+        }
+
+        // append letters until the name is neither free in the body nor in the replacement
+        let body_vars: HashSet<String> = body
+            .free_variables()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let mut fresh = v.clone();
+        while fresh == var
+            || replacement_vars.contains(fresh.as_str())
+            || body_vars.contains(&fresh)
+        {
+            fresh.push('a');
+        }
+        let body = body.substitute(&v, &Expr::Var(fresh.clone()));
+        quantifier(fresh, Box::new(body.substitute(var, replacement)))
+    }
+
     /// Returns a set of all free variable names in the formula.
     pub fn free_variables(&self) -> HashSet<&str> {
         let mut bound = HashSet::new();
@@ -181,12 +761,15 @@ impl Formula {
         free: &mut HashSet<&'a str>,
     ) {
         match self {
-            Formula::Forall(var, body) | Formula::Exists(var, body) => {
+            Formula::Forall(var, body)
+            | Formula::Exists(var, body)
+            | Formula::ForallIn(var, _, _, body)
+            | Formula::ExistsIn(var, _, _, body) => {
                 bound.insert(var.as_str());
                 body.collect_free_variables(bound, free);
                 bound.remove(var.as_str());
             }
-            Formula::And(fs) | Formula::Or(fs) => {
+            Formula::And(fs) | Formula::Or(fs) | Formula::Xor(fs) => {
                 for f in fs {
                     f.collect_free_variables(bound, free);
                 }
@@ -207,13 +790,150 @@ impl Formula {
 }
 
 impl Expr {
-    fn collect_free_variables<'a>(&'a self, bound: &HashSet<&'a str>, free: &mut HashSet<&'a str>) {
+    /// Returns the period of the expression's value in its variable, if it is periodic.
+    /// See [`Formula::period`].
+    pub fn period(&self) -> Option<usize> {
+        match self {
+            Expr::Add(e1, e2) | Expr::Sub(e1, e2) | Expr::Min(e1, e2) | Expr::Max(e1, e2) => {
+                Some(lcm(e1.period()?, e2.period()?))
+            }
+            Expr::Ite(cond, e1, e2) => Some(lcm(cond.period()?, lcm(e1.period()?, e2.period()?))),
+            Expr::MulConst(_, e) => e.period(),
+            Expr::Mod(e, m) => e.period().or_else(|| {
+                let (a, b) = e.affine_form()?;
+                let same_sign = (a >= 0 && b >= 0) || (a <= 0 && b <= 0);
+                (same_sign && *m != 0).then(|| m.unsigned_abs() as usize)
+            }),
+            Expr::Var(_) => None,
+            Expr::Const(_) => Some(1),
+        }
+    }
+
+    /// Evaluates the expression with the variable values in `env`, see [`Formula::try_eval`].
+    pub fn try_eval(&self, env: &HashMap<String, i64>) -> Result<i64, String> {
+        let overflow = || format!("arithmetic overflow in {}", self);
+        match self {
+            Expr::Add(e1, e2) => e1
+                .try_eval(env)?
+                .checked_add(e2.try_eval(env)?)
+                .ok_or_else(overflow),
+            Expr::Sub(e1, e2) => e1
+                .try_eval(env)?
+                .checked_sub(e2.try_eval(env)?)
+                .ok_or_else(overflow),
+            Expr::MulConst(n, e) => n.checked_mul(e.try_eval(env)?).ok_or_else(overflow),
+            Expr::Mod(_, 0) => Err(format!("modulo by zero in {}", self)),
+            Expr::Mod(e, n) => Ok(e.try_eval(env)?.wrapping_rem(*n)),
+            Expr::Min(e1, e2) => Ok(e1.try_eval(env)?.min(e2.try_eval(env)?)),
+            Expr::Max(e1, e2) => Ok(e1.try_eval(env)?.max(e2.try_eval(env)?)),
+            Expr::Ite(cond, e1, e2) => match cond.try_eval(env)? {
+                true => e1.try_eval(env),
+                false => e2.try_eval(env),
+            },
+            Expr::Var(v) => env
+                .get(v)
+                .copied()
+                .ok_or_else(|| format!("variable {} has no value", v)),
+            Expr::Const(n) => Ok(*n),
+        }
+    }
+
+    /// Returns `(a, b)` such that the expression equals `a * t + b`, if it is built
+    /// only from `+`, `-`, `*`, variables and constants (every variable counting as `t`)
+    /// and the coefficients do not overflow.
+    fn affine_form(&self) -> Option<(i64, i64)> {
+        match self {
+            Expr::Add(e1, e2) => {
+                let ((a1, b1), (a2, b2)) = (e1.affine_form()?, e2.affine_form()?);
+                Some((a1.checked_add(a2)?, b1.checked_add(b2)?))
+            }
+            Expr::Sub(e1, e2) => {
+                let ((a1, b1), (a2, b2)) = (e1.affine_form()?, e2.affine_form()?);
+                Some((a1.checked_sub(a2)?, b1.checked_sub(b2)?))
+            }
+            Expr::MulConst(n, e) => {
+                let (a, b) = e.affine_form()?;
+                Some((n.checked_mul(a)?, n.checked_mul(b)?))
+            }
+            Expr::Mod(_, _) | Expr::Min(_, _) | Expr::Max(_, _) | Expr::Ite(_, _, _) => None,
+            Expr::Var(_) => Some((1, 0)),
+            Expr::Const(n) => Some((0, *n)),
+        }
+    }
+
+    /// Replaces every free occurrence of `var` by `replacement`, see [`Formula::substitute`].
+    pub fn substitute(self, var: &str, replacement: &Expr) -> Expr {
+        let sub = |e: Box<Expr>| Box::new(e.substitute(var, replacement));
+        match self {
+            Expr::Add(e1, e2) => Expr::Add(sub(e1), sub(e2)),
+            Expr::Sub(e1, e2) => Expr::Sub(sub(e1), sub(e2)),
+            Expr::MulConst(n, e) => Expr::MulConst(n, sub(e)),
+            Expr::Mod(e, n) => Expr::Mod(sub(e), n),
+            Expr::Min(e1, e2) => Expr::Min(sub(e1), sub(e2)),
+            Expr::Max(e1, e2) => Expr::Max(sub(e1), sub(e2)),
+            Expr::Ite(cond, e1, e2) => Expr::Ite(
+                Box::new(cond.substitute(var, replacement)),
+                sub(e1),
+                sub(e2),
+            ),
+            Expr::Var(v) if v == var => replacement.clone(),
+            Expr::Var(v) => Expr::Var(v),
+            Expr::Const(n) => Expr::Const(n),
+        }
+    }
+
+    /// Returns a set of all free variable names in the expression.
+    pub fn free_variables(&self) -> HashSet<&str> {
+        let mut free = HashSet::new();
+        self.collect_free_variables(&mut HashSet::new(), &mut free);
+        free
+    }
+
+    /// Returns true if the conditions of all `ite` subexpressions are quantifier-free.
+    fn is_quantifier_free(&self) -> bool {
         match self {
-            Expr::Add(e1, e2) | Expr::Sub(e1, e2) => {
+            Expr::Add(e1, e2) | Expr::Sub(e1, e2) | Expr::Min(e1, e2) | Expr::Max(e1, e2) => {
+                e1.is_quantifier_free() && e2.is_quantifier_free()
+            }
+            Expr::MulConst(_, e) | Expr::Mod(e, _) => e.is_quantifier_free(),
+            Expr::Ite(cond, e1, e2) => {
+                cond.is_quantifier_free() && e1.is_quantifier_free() && e2.is_quantifier_free()
+            }
+            Expr::Var(_) | Expr::Const(_) => true,
+        }
+    }
+
+    // Returns true if the expression contains `(mod e 0)`, see `Formula::has_modulo_by_zero`.
+    fn has_modulo_by_zero(&self) -> bool {
+        match self {
+            Expr::Add(e1, e2) | Expr::Sub(e1, e2) | Expr::Min(e1, e2) | Expr::Max(e1, e2) => {
+                e1.has_modulo_by_zero() || e2.has_modulo_by_zero()
+            }
+            Expr::MulConst(_, e) => e.has_modulo_by_zero(),
+            Expr::Mod(e, m) => *m == 0 || e.has_modulo_by_zero(),
+            Expr::Ite(cond, e1, e2) => {
+                cond.has_modulo_by_zero() || e1.has_modulo_by_zero() || e2.has_modulo_by_zero()
+            }
+            Expr::Var(_) | Expr::Const(_) => false,
+        }
+    }
+
+    fn collect_free_variables<'a>(
+        &'a self,
+        bound: &mut HashSet<&'a str>,
+        free: &mut HashSet<&'a str>,
+    ) {
+        match self {
+            Expr::Add(e1, e2) | Expr::Sub(e1, e2) | Expr::Min(e1, e2) | Expr::Max(e1, e2) => {
                 e1.collect_free_variables(bound, free);
                 e2.collect_free_variables(bound, free);
             }
             Expr::MulConst(_, e) | Expr::Mod(e, _) => e.collect_free_variables(bound, free),
+            Expr::Ite(cond, e1, e2) => {
+                cond.collect_free_variables(bound, free);
+                e1.collect_free_variables(bound, free);
+                e2.collect_free_variables(bound, free);
+            }
             Expr::Var(v) => {
                 if !bound.contains(v.as_str()) {
                     free.insert(v.as_str());
@@ -224,10 +944,151 @@ impl Expr {
     }
 }
 
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Least common multiple of two positive integers.
+pub(crate) fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
+
+/// Prints the expression in the prefix syntax of the formula parser.
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Add(e1, e2) => write!(f, "(+ {} {})", e1, e2),
+            Expr::Sub(e1, e2) => write!(f, "(- {} {})", e1, e2),
+            Expr::MulConst(n, e) => write!(f, "(* {} {})", n, e),
+            Expr::Mod(e, n) => write!(f, "(mod {} {})", e, n),
+            Expr::Min(e1, e2) => write!(f, "(min {} {})", e1, e2),
+            Expr::Max(e1, e2) => write!(f, "(max {} {})", e1, e2),
+            Expr::Ite(cond, e1, e2) => write!(f, "(ite {} {} {})", cond, e1, e2),
+            Expr::Var(v) => write!(f, "{}", v),
+            Expr::Const(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// Prints the formula in the prefix syntax of the formula parser.
+/// `True` and `False` are printed as its keywords `true` and `false`.
+impl std::fmt::Display for Formula {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |f: &mut std::fmt::Formatter<'_>, op: &str, fs: &[Formula]| {
+            write!(f, "({}", op)?;
+            for sub in fs {
+                write!(f, " {}", sub)?;
+            }
+            write!(f, ")")
+        };
+        match self {
+            Formula::Forall(v, body) => write!(f, "(forall {} {})", v, body),
+            Formula::Exists(v, body) => write!(f, "(exists {} {})", v, body),
+            Formula::ForallIn(v, lo, hi, body) => {
+                write!(f, "(forall {} in {} {} {})", v, lo, hi, body)
+            }
+            Formula::ExistsIn(v, lo, hi, body) => {
+                write!(f, "(exists {} in {} {} {})", v, lo, hi, body)
+            }
+            Formula::And(fs) => list(f, "and", fs),
+            Formula::Or(fs) => list(f, "or", fs),
+            Formula::Xor(fs) => list(f, "xor", fs),
+            Formula::Not(body) => write!(f, "(not {})", body),
+            Formula::Eq(e1, e2) => write!(f, "(= {} {})", e1, e2),
+            Formula::Neq(e1, e2) => write!(f, "(!= {} {})", e1, e2),
+            Formula::Lt(e1, e2) => write!(f, "(< {} {})", e1, e2),
+            Formula::Le(e1, e2) => write!(f, "(<= {} {})", e1, e2),
+            Formula::Gt(e1, e2) => write!(f, "(> {} {})", e1, e2),
+            Formula::Ge(e1, e2) => write!(f, "(>= {} {})", e1, e2),
+            Formula::True => write!(f, "true"),
+            Formula::False => write!(f, "false"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_as_ap_union() {
+        let var = |v: &str| Box::new(Expr::Var(v.to_string()));
+        let residue =
+            |m, r| Formula::Eq(Box::new(Expr::Mod(var("t"), m)), Box::new(Expr::Const(r)));
+
+        // (or (= (mod t 3) 0) (= (mod t 5) 1))
+        let f = Formula::Or(vec![residue(3, 0), residue(5, 1)]);
+        assert_eq!(f.as_ap_union("t"), Some(vec![(3, 0), (5, 1)]));
+        let closure = f.clone().as_closure().unwrap();
+        let aps = f.as_ap_union("t").unwrap();
+        for t in 0..60 {
+            assert_eq!(aps.iter().any(|&(m, r)| t % m == r), closure(t));
+        }
+
+        // a single atom, the other way round, and a negative modulus
+        let f = Formula::Eq(Box::new(Expr::Const(2)), Box::new(Expr::Mod(var("t"), -4)));
+        assert_eq!(f.as_ap_union("t"), Some(vec![(4, 2)]));
+        // residues that never occur are left out
+        let f = Formula::Or(vec![residue(3, 3), residue(3, -1), residue(2, 1)]);
+        assert_eq!(f.as_ap_union("t"), Some(vec![(2, 1)]));
+
+        // other shapes
+        assert_eq!(residue(3, 0).as_ap_union("x"), None);
+        assert_eq!(residue(0, 0).as_ap_union("t"), None);
+        let f = Formula::Or(vec![
+            residue(3, 0),
+            Formula::Ge(var("t"), Box::new(Expr::Const(5))),
+        ]);
+        assert_eq!(f.as_ap_union("t"), None);
+        let f = Formula::Eq(
+            Box::new(Expr::Mod(Box::new(Expr::Add(var("t"), var("t"))), 3)),
+            Box::new(Expr::Const(0)),
+        );
+        assert_eq!(f.as_ap_union("t"), None);
+    }
+
+    #[test]
+    fn test_simplify() {
+        let var = |v: &str| Box::new(Expr::Var(v.to_string()));
+        let c = |n| Box::new(Expr::Const(n));
+        let late = || Formula::Ge(var("t"), c(5));
+        let even = || Formula::Eq(Box::new(Expr::Mod(var("t"), 2)), c(0));
+
+        // nested `and`s with a tautology
+        let f = Formula::And(vec![
+            late(),
+            Formula::And(vec![Formula::True, even()]),
+            Formula::Lt(c(1), c(2)),
+        ]);
+        assert_eq!(f.simplify(), Formula::And(vec![late(), even()]));
+        // an absorbing child
+        let f = Formula::Or(vec![late(), Formula::Not(Box::new(Formula::False))]);
+        assert_eq!(f.simplify(), Formula::True);
+        let f = Formula::And(vec![late(), Formula::Eq(c(1), c(2))]);
+        assert_eq!(f.simplify(), Formula::False);
+        // single children and double negations
+        let f = Formula::Or(vec![
+            Formula::False,
+            Formula::Not(Box::new(Formula::Not(Box::new(late())))),
+        ]);
+        assert_eq!(f.simplify(), late());
+        assert_eq!(Formula::And(vec![]).simplify(), Formula::True);
+        assert_eq!(Formula::Or(vec![]).simplify(), Formula::False);
+        // `xor` with constants
+        let f = Formula::Xor(vec![Formula::True, late(), Formula::False]);
+        assert_eq!(f.simplify(), Formula::Not(Box::new(late())));
+        assert_eq!(
+            Formula::Xor(vec![Formula::True, Formula::True]).simplify(),
+            Formula::False
+        );
+        // quantifier bodies
+        let f = Formula::ForallIn("y".to_string(), 0, 2, Box::new(Formula::And(vec![late()])));
+        assert_eq!(
+            f.simplify(),
+            Formula::ForallIn("y".to_string(), 0, 2, Box::new(late()))
+        );
+    }
+
     #[test]
     fn test_is_quantifier_free() {
         // Quantifier-free formula: Eq
@@ -333,14 +1194,14 @@ mod tests {
             Box::new(Expr::Const(5)),
         );
         let closure = f.as_closure().expect("Should succeed");
-        assert_eq!(closure(3), true);
-        assert_eq!(closure(2), false);
+        assert!(closure(3));
+        assert!(!closure(2));
 
         // Quantifier-free, no free variable
         let f2 = Formula::True;
         let closure2 = f2.as_closure().expect("Should succeed");
-        assert_eq!(closure2(0), true);
-        assert_eq!(closure2(42), true);
+        assert!(closure2(0));
+        assert!(closure2(42));
 
         // Not quantifier-free
         let f3 = Formula::Forall(
@@ -363,14 +1224,507 @@ mod tests {
         assert!(f4.as_closure().is_err());
     }
 
- #[test]
+    #[test]
     fn test_as_closure_ge_5() {
         let f = Formula::Ge(
             Box::new(Expr::Var("x".to_string())),
             Box::new(Expr::Const(5)),
         );
         let fun = f.as_closure().expect("Should succeed");
-        assert_eq!(fun(5),true);
-        assert_eq!(fun(4),false);
+        assert!(fun(5));
+        assert!(!fun(4));
+    }
+
+    #[test]
+    fn test_period() {
+        let t = || Box::new(Expr::Var("t".to_string()));
+
+        // (= (mod t 2) 0)
+        let even = Formula::Eq(Box::new(Expr::Mod(t(), 2)), Box::new(Expr::Const(0)));
+        assert_eq!(even.period(), Some(2));
+
+        // (or (= (mod t 2) 0) (= (mod (+ t 1) 3) 0))
+        let f = Formula::Or(vec![
+            even.clone(),
+            Formula::Eq(
+                Box::new(Expr::Mod(
+                    Box::new(Expr::Add(t(), Box::new(Expr::Const(1)))),
+                    3,
+                )),
+                Box::new(Expr::Const(0)),
+            ),
+        ]);
+        assert_eq!(f.period(), Some(6));
+
+        // constants are trivially periodic
+        assert_eq!(Formula::True.period(), Some(1));
+
+        // (>= t 5) is not periodic
+        let ge = Formula::Ge(t(), Box::new(Expr::Const(5)));
+        assert_eq!(ge.period(), None);
+        assert_eq!(Formula::And(vec![even, ge]).period(), None);
+
+        // (= (mod (- t 5) 3) 2) has a different residue pattern below t = 5
+        let shifted = Formula::Eq(
+            Box::new(Expr::Mod(
+                Box::new(Expr::Sub(t(), Box::new(Expr::Const(5)))),
+                3,
+            )),
+            Box::new(Expr::Const(2)),
+        );
+        assert_eq!(shifted.period(), None);
+
+        // (= (mod (- (- 0 t) 1) 3) -2): the argument is negative for every t >= 0
+        let negative = Formula::Eq(
+            Box::new(Expr::Mod(
+                Box::new(Expr::Sub(
+                    Box::new(Expr::Sub(Box::new(Expr::Const(0)), t())),
+                    Box::new(Expr::Const(1)),
+                )),
+                3,
+            )),
+            Box::new(Expr::Const(-2)),
+        );
+        assert_eq!(negative.period(), Some(3));
+    }
+
+    #[test]
+    fn test_tautology_and_contradiction() {
+        let t = || Box::new(Expr::Var("t".to_string()));
+        let five = || Box::new(Expr::Const(5));
+        let zero = || Box::new(Expr::Const(0));
+
+        // (or (>= t 0) (< t 0))
+        let taut = Formula::Or(vec![Formula::Ge(t(), zero()), Formula::Lt(t(), zero())]);
+        assert!(taut.is_tautology_over("t", 20));
+        assert!(!taut.is_contradiction_over("t", 20));
+
+        // (and (>= t 5) (< t 5))
+        let contra = Formula::And(vec![Formula::Ge(t(), five()), Formula::Lt(t(), five())]);
+        assert!(contra.is_contradiction_over("t", 20));
+        assert!(!contra.is_tautology_over("t", 20));
+
+        // (>= t 5) is neither, but looks like a contradiction over 0..=4
+        let ge = Formula::Ge(t(), five());
+        assert!(!ge.is_tautology_over("t", 20));
+        assert!(!ge.is_contradiction_over("t", 20));
+        assert!(ge.is_contradiction_over("t", 4));
+
+        // formulas over another variable are not decided
+        assert!(!taut.is_tautology_over("x", 20));
+        assert!(!contra.is_contradiction_over("x", 20));
+    }
+
+    #[test]
+    fn test_min_max_closure() {
+        let x = || Box::new(Expr::Var("x".to_string()));
+        let three = || Box::new(Expr::Const(3));
+
+        // (= (max x 3) 3) holds exactly for x <= 3
+        let f = Formula::Eq(Box::new(Expr::Max(x(), three())), three())
+            .as_closure()
+            .unwrap();
+        assert!((0..=3).all(&f));
+        assert!(!(4..10).any(&f));
+
+        // (= (min x 3) 3) holds exactly for x >= 3
+        let f = Formula::Eq(Box::new(Expr::Min(x(), three())), three())
+            .as_closure()
+            .unwrap();
+        assert!(!(0..3).any(&f));
+        assert!((3..10).all(f));
+
+        let f = Formula::Eq(Box::new(Expr::Max(x(), three())), three());
+        assert_eq!(f.free_variables(), HashSet::from(["x"]));
+        assert_eq!(f.period(), None);
+    }
+
+    #[test]
+    fn test_xor_closure() {
+        let t = || Box::new(Expr::Var("t".to_string()));
+        let divisible = |m| Formula::Eq(Box::new(Expr::Mod(t(), m)), Box::new(Expr::Const(0)));
+
+        // (xor (= (mod t 2) 0) (= (mod t 3) 0)): even or divisible by 3, but not both
+        let f = Formula::Xor(vec![divisible(2), divisible(3)]);
+        assert_eq!(f.free_variables(), HashSet::from(["t"]));
+        assert!(f.is_quantifier_free());
+        assert_eq!(f.period(), Some(6));
+        let closure = f.as_closure().unwrap();
+        let available: Vec<usize> = (0..13).filter(|&t| closure(t)).collect();
+        assert_eq!(available, [2, 3, 4, 8, 9, 10]);
+
+        // an odd number of children must hold
+        let f = Formula::Xor(vec![Formula::True, Formula::True, Formula::True])
+            .as_closure()
+            .unwrap();
+        assert!(f(0));
+        let f = Formula::Xor(vec![divisible(2)]).as_closure().unwrap();
+        assert!(f(0) && !f(1));
+        let f = Formula::Xor(vec![]).as_closure().unwrap();
+        assert!(!f(0));
+    }
+
+    #[test]
+    fn test_as_interval() {
+        let t = || Box::new(Expr::Var("t".to_string()));
+        let c = |n| Box::new(Expr::Const(n));
+
+        let f = Formula::And(vec![Formula::Ge(t(), c(3)), Formula::Le(t(), c(7))]);
+        assert_eq!(f.as_interval("t"), Some((Some(3), Some(7))));
+        // strict and mirrored bounds, the tightest of several
+        let f = Formula::And(vec![
+            Formula::Gt(t(), c(2)),
+            Formula::Gt(c(9), t()),
+            Formula::Le(t(), c(20)),
+            Formula::Ge(t(), c(1)),
+        ]);
+        assert_eq!(f.as_interval("t"), Some((Some(3), Some(8))));
+        assert_eq!(
+            Formula::Eq(c(4), t()).as_interval("t"),
+            Some((Some(4), Some(4)))
+        );
+        assert_eq!(Formula::Ge(t(), c(-2)).as_interval("t"), Some((None, None)));
+        assert_eq!(Formula::True.as_interval("t"), Some((None, None)));
+        // empty
+        let f = Formula::And(vec![Formula::Ge(t(), c(7)), Formula::Le(t(), c(3))]);
+        assert_eq!(f.as_interval("t"), Some((Some(1), Some(0))));
+        assert_eq!(
+            Formula::Lt(t(), c(0)).as_interval("t"),
+            Some((Some(1), Some(0)))
+        );
+
+        // not interval-shaped
+        assert_eq!(Formula::Ge(t(), c(3)).as_interval("x"), None);
+        assert_eq!(Formula::Neq(t(), c(3)).as_interval("t"), None);
+        let f = Formula::Or(vec![Formula::Le(t(), c(3)), Formula::Ge(t(), c(7))]);
+        assert_eq!(f.as_interval("t"), None);
+        let f = Formula::Ge(Box::new(Expr::Add(t(), c(1))), c(3));
+        assert_eq!(f.as_interval("t"), None);
+    }
+
+    #[test]
+    fn test_solution_set_up_to() {
+        let t = || Box::new(Expr::Var("t".to_string()));
+        let c = |n| Box::new(Expr::Const(n));
+
+        // interval-shaped
+        let f = Formula::And(vec![Formula::Ge(t(), c(3)), Formula::Le(t(), c(7))]);
+        assert_eq!(f.solution_set_up_to("t", 10), Some(vec![3, 4, 5, 6, 7]));
+        assert_eq!(f.solution_set_up_to("t", 5), Some(vec![3, 4, 5]));
+        let empty = Formula::And(vec![Formula::Ge(t(), c(7)), Formula::Le(t(), c(3))]);
+        assert_eq!(empty.solution_set_up_to("t", 10), Some(vec![]));
+
+        // (>= (* 2 t) (+ t 5)): the time variable on both sides
+        let f = Formula::Ge(
+            Box::new(Expr::MulConst(2, t())),
+            Box::new(Expr::Add(t(), c(5))),
+        );
+        assert_eq!(f.as_interval("t"), None);
+        assert_eq!(f.solution_set_up_to("t", 8), Some(vec![5, 6, 7, 8]));
+        let closure = f.clone().as_closure().unwrap();
+        assert!(!(0..5).any(&closure));
+        assert!((5..100).all(&closure));
+
+        // another free variable
+        assert_eq!(f.solution_set_up_to("x", 8), None);
+    }
+
+    #[test]
+    fn test_ite_closure() {
+        let t = || Box::new(Expr::Var("t".to_string()));
+        let c = |n| Box::new(Expr::Const(n));
+
+        // (= (ite (< t 5) 1 2) 2) flips to true at time 5
+        let ite = Expr::Ite(Box::new(Formula::Lt(t(), c(5))), c(1), c(2));
+        let f = Formula::Eq(Box::new(ite.clone()), c(2))
+            .as_closure()
+            .unwrap();
+        assert!(!(0..5).any(&f));
+        assert!((5..10).all(f));
+
+        // (= t (ite (>= t 3) t 0)) holds at 0 and from 3 on
+        let f = Formula::Eq(
+            t(),
+            Box::new(Expr::Ite(Box::new(Formula::Ge(t(), c(3))), t(), c(0))),
+        );
+        assert_eq!(f.free_variables(), HashSet::from(["t"]));
+        let f = f.as_closure().unwrap();
+        assert_eq!(
+            (0..6).map(f).collect::<Vec<_>>(),
+            [true, false, false, true, true, true]
+        );
+
+        // the variables of the condition are free variables as well
+        let ite = Expr::Ite(
+            Box::new(Formula::Lt(Box::new(Expr::Var("x".to_string())), c(5))),
+            t(),
+            c(2),
+        );
+        let f = Formula::Eq(Box::new(ite), c(2));
+        assert_eq!(f.free_variables(), HashSet::from(["t", "x"]));
+    }
+
+    #[test]
+    fn test_ite_quantified_condition() {
+        let c = |n| Box::new(Expr::Const(n));
+        let cond = Formula::Exists(
+            "y".to_string(),
+            Box::new(Formula::Eq(Box::new(Expr::Var("y".to_string())), c(0))),
+        );
+        let f = Formula::Eq(Box::new(Expr::Ite(Box::new(cond), c(1), c(2))), c(1));
+        assert!(!f.is_quantifier_free());
+        assert!(f.as_closure().is_err());
+    }
+
+    #[test]
+    fn test_is_satisfiable_in() {
+        // (>= t 5)
+        let ge = Formula::Ge(
+            Box::new(Expr::Var("t".to_string())),
+            Box::new(Expr::Const(5)),
+        );
+        assert!(!ge.is_satisfiable_in("t", 0..=4));
+        assert!(ge.is_satisfiable_in("t", 0..=5));
+        assert!(ge.is_satisfiable_in("t", 7..=7));
+        assert!(!ge.is_satisfiable_in("x", 0..=10));
+        assert!(!Formula::False.is_satisfiable_in("t", 0..=10));
+    }
+
+    #[test]
+    fn test_logically_eq() {
+        let t = || Box::new(Expr::Var("t".to_string()));
+        let c = |n| Box::new(Expr::Const(n));
+
+        // (and (>= t 2) (< t 7)) and (and (< t 7) (>= t 2))
+        let a = Formula::And(vec![Formula::Ge(t(), c(2)), Formula::Lt(t(), c(7))]);
+        let b = Formula::And(vec![Formula::Lt(t(), c(7)), Formula::Ge(t(), c(2))]);
+        assert_ne!(a, b);
+        assert!(a.logically_eq(&b, "t", 20));
+
+        // (and (>= t 2) (< t 8)) differs at time 7
+        let d = Formula::And(vec![Formula::Ge(t(), c(2)), Formula::Lt(t(), c(8))]);
+        assert!(!a.logically_eq(&d, "t", 20));
+        // ... but not within 0..=6
+        assert!(a.logically_eq(&d, "t", 6));
+    }
+
+    #[test]
+    fn test_substitute() {
+        let var = |v: &str| Box::new(Expr::Var(v.to_string()));
+        // x := (+ y 1)
+        let y_plus_one = Expr::Add(var("y"), Box::new(Expr::Const(1)));
+
+        // (>= x 5)
+        let f = Formula::Ge(var("x"), Box::new(Expr::Const(5)));
+        assert_eq!(f.substitute("x", &y_plus_one).to_string(), "(>= (+ y 1) 5)");
+
+        // bound occurrences stay: (and (= x 0) (forall x (= x 1)))
+        let f = Formula::And(vec![
+            Formula::Eq(var("x"), Box::new(Expr::Const(0))),
+            Formula::Forall(
+                "x".to_string(),
+                Box::new(Formula::Eq(var("x"), Box::new(Expr::Const(1)))),
+            ),
+        ]);
+        assert_eq!(
+            f.substitute("x", &y_plus_one).to_string(),
+            "(and (= (+ y 1) 0) (forall x (= x 1)))"
+        );
+
+        // inside ite conditions
+        let f = Formula::Eq(
+            Box::new(Expr::Ite(
+                Box::new(Formula::Lt(var("x"), Box::new(Expr::Const(3)))),
+                var("x"),
+                var("z"),
+            )),
+            var("z"),
+        );
+        assert_eq!(
+            f.substitute("x", &y_plus_one).to_string(),
+            "(= (ite (< (+ y 1) 3) (+ y 1) z) z)"
+        );
+    }
+
+    #[test]
+    fn test_substitute_avoids_capture() {
+        let var = |v: &str| Box::new(Expr::Var(v.to_string()));
+        let y_plus_one = Expr::Add(var("y"), Box::new(Expr::Const(1)));
+
+        // (exists y (= x y)): the y of the replacement must stay free
+        let f = Formula::Exists("y".to_string(), Box::new(Formula::Eq(var("x"), var("y"))));
+        let g = f.substitute("x", &y_plus_one);
+        assert_eq!(g.to_string(), "(exists ya (= (+ y 1) ya))");
+        assert_eq!(g.free_variables(), HashSet::from(["y"]));
+
+        // the fresh name must not clash with free variables of the body either
+        let f = Formula::Exists(
+            "y".to_string(),
+            Box::new(Formula::And(vec![
+                Formula::Eq(var("x"), var("y")),
+                Formula::Eq(var("ya"), var("y")),
+            ])),
+        );
+        assert_eq!(
+            f.substitute("x", &y_plus_one).to_string(),
+            "(exists yaa (and (= (+ y 1) yaa) (= ya yaa)))"
+        );
+    }
+
+    #[test]
+    fn test_as_closure_bounded() {
+        let var = |v: &str| Box::new(Expr::Var(v.to_string()));
+        let domains = HashMap::from([("y".to_string(), 0..=2)]);
+
+        // (forall y (>= t y)) with y in 0..=2 holds from t = 2 on
+        let f = Formula::Forall("y".to_string(), Box::new(Formula::Ge(var("t"), var("y"))));
+        assert!(f.clone().as_closure().is_err());
+        let closure = f.as_closure_bounded(domains.clone()).unwrap();
+        assert_eq!(
+            (0..5).map(closure).collect::<Vec<_>>(),
+            [false, false, true, true, true]
+        );
+
+        // (exists y (= t (* 2 y))) with y in 0..=2: t is one of 0, 2, 4
+        let f = Formula::Exists(
+            "y".to_string(),
+            Box::new(Formula::Eq(var("t"), Box::new(Expr::MulConst(2, var("y"))))),
+        );
+        let closure = f.as_closure_bounded(domains.clone()).unwrap();
+        let holds: Vec<usize> = (0..8).filter(|&t| closure(t)).collect();
+        assert_eq!(holds, [0, 2, 4]);
+
+        // a quantified variable without domain
+        let f = Formula::Forall("z".to_string(), Box::new(Formula::Ge(var("t"), var("z"))));
+        assert!(f.as_closure_bounded(domains).is_err());
+    }
+
+    #[test]
+    fn test_overflow() {
+        let var = |v: &str| Box::new(Expr::Var(v.to_string()));
+        let max = || Box::new(Expr::Const(i64::MAX));
+
+        // t + i64::MAX saturates instead of wrapping around to a negative number
+        let f = Formula::Gt(
+            Box::new(Expr::Add(var("t"), max())),
+            Box::new(Expr::Const(0)),
+        );
+        let closure = f.clone().as_closure().unwrap();
+        assert!((0..3).all(closure));
+        // 2 * (0 - i64::MAX - t) saturates at i64::MIN
+        let f2 = Formula::Eq(
+            Box::new(Expr::MulConst(
+                2,
+                Box::new(Expr::Sub(
+                    Box::new(Expr::Sub(Box::new(Expr::Const(0)), max())),
+                    var("t"),
+                )),
+            )),
+            Box::new(Expr::Const(i64::MIN)),
+        );
+        assert!(f2.clone().as_closure().unwrap()(5));
+
+        let env = |t: i64| HashMap::from([("t".to_string(), t)]);
+        assert_eq!(f.try_eval(&env(0)), Ok(true));
+        let err = f.try_eval(&env(1)).unwrap_err();
+        assert!(
+            err.contains("overflow in (+ t 9223372036854775807)"),
+            "{}",
+            err
+        );
+        assert!(f2.try_eval(&env(5)).is_err());
+
+        // (= (mod t 0) 0) has no closure, and try_eval reports the modulo by zero
+        let f3 = Formula::Eq(Box::new(Expr::Mod(var("t"), 0)), Box::new(Expr::Const(0)));
+        assert!(f3.clone().as_closure().is_err());
+        let err = f3.try_eval(&env(1)).unwrap_err();
+        assert!(err.contains("modulo by zero"), "{}", err);
+    }
+
+    #[test]
+    fn test_try_eval() {
+        let var = |v: &str| Box::new(Expr::Var(v.to_string()));
+        let env = HashMap::from([("t".to_string(), 4), ("y".to_string(), 2)]);
+
+        // several variables
+        let f = Formula::Eq(Box::new(Expr::MulConst(2, var("y"))), var("t"));
+        assert_eq!(f.try_eval(&env), Ok(true));
+        let f = Formula::ExistsIn(
+            "z".to_string(),
+            0,
+            3,
+            Box::new(Formula::Eq(
+                Box::new(Expr::Add(var("y"), var("z"))),
+                var("t"),
+            )),
+        );
+        assert_eq!(f.try_eval(&env), Ok(true));
+
+        let f = Formula::Ge(var("t"), var("x"));
+        assert_eq!(f.try_eval(&env), Err("variable x has no value".to_string()));
+        let f = Formula::Eq(Box::new(Expr::Mod(var("t"), 0)), Box::new(Expr::Const(0)));
+        assert!(f.try_eval(&env).is_err());
+        let f = Formula::Forall("z".to_string(), Box::new(Formula::True));
+        assert!(f.try_eval(&env).is_err());
+    }
+
+    #[test]
+    fn test_constant_value() {
+        let c = |n: i64| Box::new(Expr::Const(n));
+        assert_eq!(Formula::Ge(c(5), c(3)).constant_value(), Some(true));
+        assert_eq!(Formula::Ge(c(3), c(5)).constant_value(), Some(false));
+        assert_eq!(Formula::Ge(c(3), c(5)).fold_constant(), Formula::False);
+        let f = Formula::ForallIn(
+            "y".to_string(),
+            0,
+            2,
+            Box::new(Formula::Le(Box::new(Expr::Var("y".to_string())), c(2))),
+        );
+        assert_eq!(f.fold_constant(), Formula::True);
+
+        // a free variable, or no range to evaluate over
+        let f = Formula::Ge(Box::new(Expr::Var("t".to_string())), c(3));
+        assert_eq!(f.constant_value(), None);
+        assert_eq!(f.clone().fold_constant(), f);
+        let f = Formula::Forall("y".to_string(), Box::new(Formula::True));
+        assert_eq!(f.constant_value(), None);
+    }
+
+    #[test]
+    fn test_as_closure_ranged_quantifiers() {
+        let var = |v: &str| Box::new(Expr::Var(v.to_string()));
+
+        // (forall y in 0 2 (>= t y)) holds from t = 2 on
+        let f = Formula::ForallIn(
+            "y".to_string(),
+            0,
+            2,
+            Box::new(Formula::Ge(var("t"), var("y"))),
+        );
+        assert!(!f.is_quantifier_free());
+        assert_eq!(f.free_variables(), HashSet::from(["t"]));
+        let closure = f.as_closure().unwrap();
+        assert_eq!(
+            (0..5).map(closure).collect::<Vec<_>>(),
+            [false, false, true, true, true]
+        );
+
+        // (exists y in 1 2 (= t (* 3 y))): t is 3 or 6
+        let f = Formula::ExistsIn(
+            "y".to_string(),
+            1,
+            2,
+            Box::new(Formula::Eq(var("t"), Box::new(Expr::MulConst(3, var("y"))))),
+        );
+        let closure = f.as_closure().unwrap();
+        let holds: Vec<usize> = (0..10).filter(|&t| closure(t)).collect();
+        assert_eq!(holds, [3, 6]);
+
+        // empty ranges: forall holds trivially, exists never
+        let body = Box::new(Formula::Ge(var("t"), var("y")));
+        let f = Formula::ForallIn("y".to_string(), 1, 0, body.clone());
+        assert!(f.as_closure().unwrap()(0));
+        let f = Formula::ExistsIn("y".to_string(), 1, 0, body);
+        assert!(!f.as_closure().unwrap()(0));
     }
 }
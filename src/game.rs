@@ -1,4 +1,24 @@
-use crate::temporal_graphs::TemporalGraph;
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::bitset;
+use crate::temporal_graphs::{Node, TemporalGraph};
+
+/// How to treat an opponent node without any available edge.
+///
+/// A node owned by the reaching player without available edges is always losing for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeadlockPolicy {
+    /// The stuck opponent loses: the node is winning for the reaching player.
+    OpponentLoses,
+    /// The stuck opponent wins: the node is losing for the reaching player.
+    #[default]
+    OpponentWins,
+    /// The stuck opponent loses only at a terminal node, one without any outgoing edges;
+    /// at a node whose edges are merely unavailable at the time, it wins.
+    TerminalOpponentLoses,
+}
 
 /// Computes the reachable set at time 0 for a punctual reachability game
 /// by simple back propagation from the target set at time k.
@@ -11,173 +31,2381 @@ use crate::temporal_graphs::TemporalGraph;
 ///
 /// # Returns
 /// A vector of booleans indicating which nodes are in the winning set at time 0
-pub fn reachable_at(
+///
+/// An opponent node without available edges is losing for the reaching player
+/// ([`DeadlockPolicy::OpponentWins`]); see [`reachable_at_with_policy`].
+///
+/// For `k = 0` no move is made and the winning set is the target itself;
+/// for a graph without nodes it is empty.
+///
+/// # Panics
+/// If `target` does not have one entry per node of the graph.
+pub fn reachable_at(graph: &TemporalGraph, k: usize, player: bool, target: &[bool]) -> Vec<bool> {
+    reachable_at_with_policy(graph, k, player, target, DeadlockPolicy::OpponentWins)
+}
+
+/// A winning set, as computed by [`reachable_at_set`].
+///
+/// Wraps the `Vec<bool>` returned by [`reachable_at`], one entry per node,
+/// without keeping a reference to the graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WinningSet(Vec<bool>);
+
+impl WinningSet {
+    /// Returns true if `node` is winning.
+    pub fn contains(&self, node: Node) -> bool {
+        self.0.get(node).copied().unwrap_or(false)
+    }
+
+    /// Returns the ids of the winning nodes; `graph` must be the solved graph.
+    pub fn ids(&self, graph: &TemporalGraph) -> HashSet<String> {
+        graph.ids_from_nodes_vec(&self.0)
+    }
+
+    /// Returns the number of winning nodes.
+    pub fn count(&self) -> usize {
+        winning_count(&self.0)
+    }
+
+    /// Returns the number of nodes of the solved graph, winning or not.
+    pub fn node_count(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the winning nodes in increasing order.
+    pub fn iter_winning_nodes(&self) -> impl Iterator<Item = Node> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| **w)
+            .map(|(n, _)| n)
+    }
+
+    /// Returns the winning set as one bool per node.
+    pub fn as_slice(&self) -> &[bool] {
+        &self.0
+    }
+}
+
+impl From<Vec<bool>> for WinningSet {
+    fn from(winning: Vec<bool>) -> Self {
+        WinningSet(winning)
+    }
+}
+
+impl From<WinningSet> for Vec<bool> {
+    fn from(winning: WinningSet) -> Self {
+        winning.0
+    }
+}
+
+/// Computes the reachable set at time 0 like [`reachable_at`], as a [`WinningSet`].
+pub fn reachable_at_set(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+) -> WinningSet {
+    WinningSet(reachable_at(graph, k, player, target))
+}
+
+/// Computes the reachable set at time 0 like [`reachable_at`],
+/// treating stuck opponent nodes according to `deadlock`.
+///
+/// If no edge availability depends on time, the iteration stops as soon as
+/// the winning set no longer changes, since every further step is identical.
+pub fn reachable_at_with_policy(
     graph: &TemporalGraph,
     k: usize,
     player: bool,
-    target: &Vec<bool>,
+    target: &[bool],
+    deadlock: DeadlockPolicy,
 ) -> Vec<bool> {
+    backward_induction(graph, k, player, target, deadlock, None)
+        .expect("backward induction without cancellation flag")
+        .0
+}
+
+/// Statistics about a run of the backward induction, see [`reachable_at_instrumented`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SolveStats {
+    /// The number of backward steps performed, at most `k`
+    pub iterations: usize,
+    /// The total number of nodes that entered or left the winning set over all steps
+    pub flips: usize,
+    /// Whether the iteration stopped before time 0 because the winning set stabilised
+    pub early_fixpoint: bool,
+}
+
+/// Computes the reachable set at time 0 like [`reachable_at`],
+/// along with statistics about the backward induction.
+pub fn reachable_at_instrumented(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+) -> (Vec<bool>, SolveStats) {
+    backward_induction(graph, k, player, target, DeadlockPolicy::OpponentWins, None)
+        .expect("backward induction without cancellation flag")
+}
+
+/// Computes the reachable set at time 0 like [`reachable_at`], but checks `cancel`
+/// before every backward step and gives up, returning `None`, once it is set.
+pub fn reachable_at_cancellable(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+    cancel: &AtomicBool,
+) -> Option<Vec<bool>> {
+    backward_induction(
+        graph,
+        k,
+        player,
+        target,
+        DeadlockPolicy::OpponentWins,
+        Some(cancel),
+    )
+    .map(|(wins_at, _)| wins_at)
+}
+
+// the backward induction behind `reachable_at`; `None` if cancelled
+fn backward_induction(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+    deadlock: DeadlockPolicy,
+    cancel: Option<&AtomicBool>,
+) -> Option<(Vec<bool>, SolveStats)> {
+    assert!(
+        target.len() == graph.node_count,
+        "target set has {} entries, but the graph has {} nodes",
+        target.len(),
+        graph.node_count
+    );
+
+    // nothing to do: no moves before time k, or no nodes to move between
+    if k == 0 || graph.node_count == 0 {
+        return Some((target.to_vec(), SolveStats::default()));
+    }
+
     // get node ownership from the graph
     let owner: Vec<bool> = graph.node_ownership();
 
-    // w is the winning set at time k
-    let mut wins_at: Vec<bool> = target.to_vec();
-    //dbg!("target: {:?}", wins_at);
+    // early termination is only sound if the attractor step is the same at every time
+    let time_invariant = graph.is_time_invariant();
+    let self_loops = graph.always_self_loops();
+
+    // terminal opponent nodes are decided once and for all by the policy
+    let terminal_wins: Vec<bool> = graph
+        .nodes()
+        .map(|n| {
+            deadlock == DeadlockPolicy::TerminalOpponentLoses
+                && owner[n] != player
+                && graph.out_degree(n) == 0
+        })
+        .collect();
+    let step_deadlock = match deadlock {
+        DeadlockPolicy::TerminalOpponentLoses => DeadlockPolicy::OpponentWins,
+        d => d,
+    };
+
+    let mut stats = SolveStats::default();
+    let mut cancelled = false;
+
+    // compute the winning set one time at a time from k-1 down to 0,
+    // starting with the target at time k
+    let wins_at = backward_iteration(graph, k, target.to_vec(), |wins_at, graph, i| {
+        if cancel.is_some_and(|c| c.load(Ordering::Relaxed)) {
+            cancelled = true;
+            return ControlFlow::Break(wins_at.to_vec());
+        }
+        let mut wins_before = cpre_by(
+            &owner,
+            player,
+            wins_at,
+            step_deadlock,
+            &self_loops,
+            |node| graph.successors_at(node, i),
+        );
+        for (w, &t) in wins_before.iter_mut().zip(&terminal_wins) {
+            *w |= t;
+        }
+        stats.iterations += 1;
+        let flips = wins_before
+            .iter()
+            .zip(wins_at)
+            .filter(|(a, b)| a != b)
+            .count();
+        stats.flips += flips;
+        if time_invariant && flips == 0 {
+            stats.early_fixpoint = i > 0;
+            return ControlFlow::Break(wins_before);
+        }
+        ControlFlow::Continue(wins_before)
+    });
 
-    // auxiliary variable for winning set at time i-1
-    let mut wins_before: Vec<bool> = vec![false; graph.node_count];
+    (!cancelled).then_some((wins_at, stats))
+}
+
+/// Runs a backward iteration from time `k` down to time 0.
+///
+/// Starting from the set `init` at time `k`, `step(set, graph, i)` computes the set
+/// at time `i` from the `set` at time `i + 1`, for `i` from `k - 1` down to 0.
+/// The solvers of this module are such iterations: for [`reachable_at`] the step is
+/// [`cpre`] and `init` the target set, for [`losing_region`] the step is the opponent's
+/// safety step and `init` the complement of the target set. Other monotone operators,
+/// e.g. adding nodes at certain times as in [`reachable_in_window`], can be plugged in.
+///
+/// # Returns
+/// The set at time 0, or `init` if `k == 0`
+pub fn backward_fixpoint(
+    graph: &TemporalGraph,
+    k: usize,
+    init: Vec<bool>,
+    mut step: impl FnMut(&[bool], &TemporalGraph, usize) -> Vec<bool>,
+) -> Vec<bool> {
+    backward_iteration(graph, k, init, |set, graph, i| {
+        ControlFlow::Continue(step(set, graph, i))
+    })
+}
 
-    // compute wins_at one at a time from k-1 down to 0
+// `backward_fixpoint`, but the step can end the iteration early with `Break(result)`
+fn backward_iteration(
+    graph: &TemporalGraph,
+    k: usize,
+    init: Vec<bool>,
+    mut step: impl FnMut(&[bool], &TemporalGraph, usize) -> ControlFlow<Vec<bool>, Vec<bool>>,
+) -> Vec<bool> {
+    let mut set = init;
     for i in (0..k).rev() {
-        // wins_before = 1-step attractor of wins_at
-        for node in graph.nodes() {
-            //let successors: Vec<_> = graph.successors_at(node, i).collect();
-            // dbg!(
-            //     "SUCCS from {} (owner {}) at {} = {:?}",
-            //     node, owner[node], i, &successors
-            // );
-            match owner[node] == player {
-                true => wins_before[node] = graph.successors_at(node, i).any(|s| wins_at[s]),
-                false => {
-                    wins_before[node] = graph.successors_at(node, i).next().is_some()
-                        && graph.successors_at(node, i).all(|s| wins_at[s])
-                }
-           }
+        match step(&set, graph, i) {
+            ControlFlow::Continue(before) => set = before,
+            ControlFlow::Break(result) => return result,
         }
-        wins_at = wins_before.clone();
-        //dbg!("{:?}", wins_at);
-        //dbg!("W_{} = {:?}", i, graph.ids_from_nodes_vec(&wins_at));
     }
+    set
+}
 
-    wins_at
+/// Computes the winning set of a punctual reachability game at every time `0..=k`,
+/// by the same backward induction as [`reachable_at`] but without early termination.
+///
+/// # Returns
+/// A vector of `k + 1` winning sets: entry `i` is the winning set at time `i`,
+/// so the first entry is the result of [`reachable_at`] and the last is `target`.
+pub fn winning_at_each_step(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+) -> Vec<Vec<bool>> {
+    let owner: Vec<bool> = graph.node_ownership();
+
+    let mut wins = vec![target.to_vec()];
+    for i in (0..k).rev() {
+        let wins_before = cpre(graph, &owner, player, wins.last().unwrap(), i);
+        wins.push(wins_before);
+    }
+    wins.reverse();
+    wins
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::formulae::Formula;
-    use crate::parser::NodeAttr;
-    use crate::temporal_graphs::Edge;
-    use std::collections::HashMap;
+/// Computes the same winning set as [`reachable_at`], but consults successor sets
+/// precomputed by [`TemporalGraph::precompute_successors`] instead of evaluating
+/// edge formulas. The table can be shared between solves on the same graph.
+///
+/// # Panics
+/// If `successors` covers fewer than `k` time steps.
+pub fn reachable_at_cached(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+    successors: &[Vec<Vec<Node>>],
+) -> Vec<bool> {
+    assert!(
+        successors.len() >= k,
+        "successors precomputed for {} time steps, but k = {}",
+        successors.len(),
+        k
+    );
+    let owner: Vec<bool> = graph.node_ownership();
+    let self_loops = graph.always_self_loops();
 
-    // Helper function to create a single-state graph owned by player 0 with a self-loop
-    // Creates: s0 (player 0) with self-loop edge that is always available (constraint "true")
-    fn create_self_loop() -> TemporalGraph {
-        let node_count = 1;
+    backward_fixpoint(graph, k, target.to_vec(), |wins_at, _, i| {
+        cpre_by(
+            &owner,
+            player,
+            wins_at,
+            DeadlockPolicy::OpponentWins,
+            &self_loops,
+            |n| successors[i][n].iter().copied(),
+        )
+    })
+}
 
-        // Create node ID mapping
-        let mut node_id_map = HashMap::new();
-        node_id_map.insert("s0".to_string(), 0);
+/// A punctual reachability game solved by backward induction, keeping the winning set
+/// at every time so that it can be updated cheaply after an edge changes.
+///
+/// Change edges through [`Solver::graph_mut`], report each changed pair of endpoints
+/// with [`Solver::invalidate_edge`], then call [`Solver::resolve`]. Only the backward
+/// steps at times where the changed edges' availability differs, and the steps below
+/// them whose winning set changes as a consequence, are recomputed.
+/// Other modifications of the graph (e.g. node ownership) are not picked up.
+pub struct Solver {
+    graph: TemporalGraph,
+    player: bool,
+    owner: Vec<bool>,
+    // successors[i][n]: the successors of n at time i, as last seen by the solver
+    successors: Vec<Vec<Vec<Node>>>,
+    // wins[i]: the winning set at time i, for i in 0..=k
+    wins: Vec<Vec<bool>>,
+    // the times at which some successor set changed since the last solve
+    dirty: Vec<usize>,
+}
 
-        // Create node attributes
-        let mut node_attrs = HashMap::new();
-        let mut s0_attrs = HashMap::new();
-        s0_attrs.insert("owner".to_string(), NodeAttr::Owner(false)); // player 0
-        s0_attrs.insert("label".to_string(), NodeAttr::Label("s0".to_string()));
-        node_attrs.insert(0, s0_attrs);
+impl Solver {
+    /// Solves the game like [`reachable_at`], keeping every intermediate winning set.
+    pub fn new(graph: TemporalGraph, k: usize, player: bool, target: &[bool]) -> Self {
+        let mut solver = Self {
+            owner: graph.node_ownership(),
+            successors: graph.precompute_successors(k),
+            wins: vec![target.to_vec(); k + 1],
+            dirty: (0..k).collect(),
+            graph,
+            player,
+        };
+        solver.resolve();
+        solver
+    }
 
-        // Create self-loop edge with constraint "true"
-        let edges = vec![Edge::new(0, 0, Formula::True)];
+    pub fn graph(&self) -> &TemporalGraph {
+        &self.graph
+    }
 
-        TemporalGraph::new(node_count, node_id_map, node_attrs, edges)
+    /// Gives mutable access to the graph; changed edges must be reported
+    /// with [`Solver::invalidate_edge`] before calling [`Solver::resolve`].
+    pub fn graph_mut(&mut self) -> &mut TemporalGraph {
+        &mut self.graph
     }
 
-    // Helper: two-state graph, both with self-loops (constraint true),
-    // and state 0 has an edge to state 1 with constraint x >= 5.
-    fn create_two_state_graph() -> TemporalGraph {
-        let node_count = 2;
-        let mut node_id_map = HashMap::new();
-        node_id_map.insert("s0".to_string(), 0);
-        node_id_map.insert("s1".to_string(), 1);
+    /// The time bound the game was solved for.
+    pub fn k(&self) -> usize {
+        self.wins.len() - 1
+    }
 
-        let mut node_attrs = HashMap::new();
-        let mut s0_attrs = HashMap::new();
-        s0_attrs.insert("owner".to_string(), NodeAttr::Owner(false));
-        s0_attrs.insert("label".to_string(), NodeAttr::Label("s0".to_string()));
-        node_attrs.insert(0, s0_attrs);
-        let mut s1_attrs = HashMap::new();
-        s1_attrs.insert("owner".to_string(), NodeAttr::Owner(false));
-        s1_attrs.insert("label".to_string(), NodeAttr::Label("s1".to_string()));
-        node_attrs.insert(1, s1_attrs);
+    /// The winning set at time 0, as of the last solve.
+    pub fn winning(&self) -> &[bool] {
+        &self.wins[0]
+    }
 
-        use crate::formulae::{Expr, Formula};
-        let edges = vec![
-            // self-loops
-            Edge::new(0, 0, Formula::True),
-            Edge::new(1, 1, Formula::True),
-            // edge from 0 to 1 with constraint x >= 5
-            Edge::new(
-                0,
-                1,
-                Formula::Ge(
-                    Box::new(Expr::Var("x".to_string())),
-                    Box::new(Expr::Const(5)),
-                ),
-            ),
-        ];
-        TemporalGraph::new(node_count, node_id_map, node_attrs, edges)
+    /// The winning set at the given time (at most `k`), as of the last solve.
+    pub fn winning_at(&self, time: usize) -> &[bool] {
+        &self.wins[time]
     }
 
-    #[test]
-    fn test_self_loop_0() {
-        let graph = create_self_loop();
+    /// Marks the edges from `from` to `to` as changed (formula edited, edge added or removed),
+    /// recording the times at which their availability now differs.
+    pub fn invalidate_edge(&mut self, from: Node, to: Node) {
+        for i in 0..self.k() {
+            let available = self.graph.successors_at(from, i).any(|n| n == to);
+            if available != self.successors[i][from].contains(&to) {
+                self.successors[i][from] = self.graph.successors_at(from, i).collect();
+                self.dirty.push(i);
+            }
+        }
+    }
 
-        // Target the single state (node 0) at time 0
-        let target = vec![true]; // node 0 is the target
-        let k = 0;
+    /// Recomputes the winning sets affected by the edges invalidated since the last solve,
+    /// returning the winning set at time 0.
+    pub fn resolve(&mut self) -> &[bool] {
+        let Some(&latest) = self.dirty.iter().max() else {
+            return &self.wins[0];
+        };
+        let earliest = *self.dirty.iter().min().unwrap();
 
-        assert_eq!(reachable_at(&graph, k, true, &target), vec![true]);
-        assert_eq!(reachable_at(&graph, k, false, &target), vec![true]);
+        // whether the winning set at time i + 1 differs from the last solve
+        let mut changed_above = false;
+        for i in (0..=latest).rev() {
+            if !changed_above && !self.dirty.contains(&i) {
+                if i < earliest {
+                    break;
+                }
+                continue;
+            }
+            let successors = &self.successors[i];
+            let wins_before = cpre_by(
+                &self.owner,
+                self.player,
+                &self.wins[i + 1],
+                DeadlockPolicy::OpponentWins,
+                &[],
+                |n| successors[n].iter().copied(),
+            );
+            changed_above = wins_before != self.wins[i];
+            self.wins[i] = wins_before;
+        }
+        self.dirty.clear();
+
+        debug_assert_eq!(
+            self.wins[0],
+            reachable_at(&self.graph, self.k(), self.player, &self.wins[self.k()]),
+            "incremental solve disagrees with a fresh solve"
+        );
+        &self.wins[0]
     }
+}
 
-    #[test]
-    fn test_self_loop_1() {
-        let graph = create_self_loop();
+/// Computes the reachable set at time 0 for a punctual reachability game
+/// in which the reaching player must never enter a forbidden node.
+///
+/// This is [`reachable_at`] with the winning set masked by `!avoid`
+/// after every backward step; a target node that is also forbidden is losing.
+///
+/// # Arguments
+/// * `graph` - Reference to the temporal graph
+/// * `k` - The time horizon (time at which to reach the target)
+/// * `player` - Boolean player who wants to reach
+/// * `target` - target set
+/// * `avoid` - forbidden set
+///
+/// # Returns
+/// A vector of booleans indicating which nodes are in the winning set at time 0
+pub fn reachable_at_avoiding(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+    avoid: &[bool],
+) -> Vec<bool> {
+    let owner: Vec<bool> = graph.node_ownership();
+    let mask = |wins: &mut Vec<bool>| {
+        for (w, a) in wins.iter_mut().zip(avoid) {
+            *w &= !a;
+        }
+    };
 
-        // Target the single state (node 0) at time 0
-        let target = vec![true]; // node 0 is the target
-        let k = 1;
+    let mut init: Vec<bool> = target.to_vec();
+    mask(&mut init);
+    backward_fixpoint(graph, k, init, |wins_at, graph, i| {
+        let mut wins_before = cpre(graph, &owner, player, wins_at, i);
+        mask(&mut wins_before);
+        wins_before
+    })
+}
 
-        assert_eq!(reachable_at(&graph, k, true, &target), vec![true]);
-        assert_eq!(reachable_at(&graph, k, false, &target), vec![true]);
+/// Computes the same winning set as [`reachable_at`], but skips redundant
+/// steps when edge availability is periodic.
+///
+/// If the graph has a global availability period `p`
+/// (see [`TemporalGraph::availability_period`]), the backward induction is
+/// determined by the current winning set and the current time modulo `p`.
+/// Once such a state repeats, the sequence of winning sets cycles and
+/// whole cycles can be skipped, so very large horizons are answered
+/// without iterating every step.
+/// This relies on availability being periodic from time 0 on, which
+/// [`Formula::period`](crate::formulae::Formula::period) only reports then.
+/// Falls back to [`reachable_at`] if availability is not periodic.
+pub fn reachable_at_periodic(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+) -> Vec<bool> {
+    let Some(period) = graph.availability_period() else {
+        return reachable_at(graph, k, player, target);
+    };
+    let owner: Vec<bool> = graph.node_ownership();
+
+    // maps (winning set at time i, i mod period) to the first i it was seen at
+    let mut seen: HashMap<(Vec<bool>, usize), usize> = HashMap::new();
+    let mut cycle_skipped = false;
+
+    let mut wins_at: Vec<bool> = target.to_vec();
+    let mut i = k;
+    while i > 0 {
+        if !cycle_skipped {
+            match seen.get(&(wins_at.clone(), i % period)) {
+                Some(&j) => {
+                    // the state at i repeats the one at j > i, and j - i is a
+                    // multiple of the period: skip all full cycles
+                    i %= j - i;
+                    cycle_skipped = true;
+                    continue;
+                }
+                None => {
+                    seen.insert((wins_at.clone(), i % period), i);
+                }
+            }
+        }
+        i -= 1;
+        wins_at = cpre(graph, &owner, player, &wins_at, i);
     }
 
-    #[test]
-    fn test_two_state_reachability() {
-        let graph = create_two_state_graph();
+    wins_at
+}
 
-        // Let state 1 be the only target
-        let target = vec![false, true];
+/// Computes the same winning set as [`reachable_at`], given that every step of the
+/// backward induction at time `horizon` or later is the same, as for the time
+/// returned by [`TemporalGraph::max_meaningful_time`].
+///
+/// Those steps are iterated only until the winning set repeats; then all full
+/// cycles are skipped, so a time bound far beyond `horizon` costs about `horizon` steps.
+pub fn reachable_at_capped(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+    horizon: usize,
+) -> Vec<bool> {
+    let owner: Vec<bool> = graph.node_ownership();
 
-        // assume perspective of player false
-        let reacher = false;
+    // maps the winning set at a time i > horizon to the first such i it was seen at
+    let mut seen: HashMap<Vec<bool>, usize> = HashMap::new();
+    let mut cycle_skipped = false;
 
-        // player false can force to reach the target at time 0 only from the target
-        assert_eq!(reachable_at(&graph, 0, reacher, &target), vec![false, true]);
-        // player false can force to reach the target at times 1-4 only from the target
-        assert_eq!(reachable_at(&graph, 1, reacher, &target), vec![false, true]);
-        assert_eq!(reachable_at(&graph, 2, reacher, &target), vec![false, true]);
-        assert_eq!(reachable_at(&graph, 3, reacher, &target), vec![false, true]);
-        assert_eq!(reachable_at(&graph, 4, reacher, &target), vec![false, true]);
+    let mut wins_at: Vec<bool> = target.to_vec();
+    let mut i = k;
+    while i > 0 {
+        if i > horizon && !cycle_skipped {
+            if let Some(&j) = seen.get(&wins_at) {
+                // every step from j down to i was the same: skip all full cycles
+                i = horizon + (i - horizon) % (j - i);
+                cycle_skipped = true;
+                continue;
+            }
+            seen.insert(wins_at.clone(), i);
+        }
+        i -= 1;
+        wins_at = cpre(graph, &owner, player, &wins_at, i);
+    }
 
-        // player false can force to reach the target at times 5 only from the target,
-        // because it would have to take the edge 0 --> 1 at time 4;
-        // it is only available from time 5 onwards.
+    wins_at
+}
 
-        assert_eq!(reachable_at(&graph, 5, reacher, &target), vec![false, true]);
+/// Computes the losing region at time 0: the nodes from which `player`
+/// cannot force reaching the target at time k.
+///
+/// This is computed independently of [`reachable_at`], as the region from which
+/// the opponent can force to avoid the target at time k (a safety game).
+/// By determinacy it is exactly the complement of the winning region,
+/// which is checked when debug assertions are enabled.
+///
+/// # Arguments
+/// * `graph` - Reference to the temporal graph
+/// * `k` - The time horizon (time at which to reach the target)
+/// * `player` - Boolean player who wants to reach
+/// * `target` - target set
+pub fn losing_region(graph: &TemporalGraph, k: usize, player: bool, target: &[bool]) -> Vec<bool> {
+    let owner: Vec<bool> = graph.node_ownership();
 
-        // player false CAN force to reach the target at time 6 and later
-        // from states 1 (target) AND 0
-        // (by wating at 0 and then taking edge 0 --> 1 at time 5)
-        assert_eq!(reachable_at(&graph, 6, reacher, &target), vec![true, true]);
-        assert_eq!(reachable_at(&graph, 7, reacher, &target), vec![true, true]);
+    // the opponent wins at time k outside the target
+    let loses_at = backward_fixpoint(graph, k, bitset::not(target), |loses_at, graph, i| {
+        losing_step(graph, &owner, player, loses_at, i)
+    });
 
-        // player !reacher == true (the opponent here) can force to reach the
-        // target only from the target, no matter when, because she does not control the edges (own
-        // state 0 in particular)
-        assert_eq!(
-            reachable_at(&graph, 7, !reacher, &target),
-            vec![false, true]
-        );
+    debug_assert!(
+        reachable_at(graph, k, player, target)
+            .iter()
+            .zip(&loses_at)
+            .all(|(w, l)| w != l),
+        "losing region is not the complement of the winning region"
+    );
+
+    loses_at
+}
+
+/// Returns, per node, the player who wins at time 0 the game in which player 0
+/// wants to be in the target at time k and player 1 wants to avoid it:
+/// `true` if player 0 wins and `false` if player 1 wins, as in the `owner` attribute.
+///
+/// Only player 1's safety game is solved, as in [`losing_region`]: the nodes from which
+/// player 1 can stay out of the target at time k are `false`. The game is determined,
+/// so all other nodes are `true`.
+/// When debug assertions are enabled, this is checked against the independent
+/// solve for player 0, [`reachable_at`]; a mismatch is a bug in the solver and panics.
+pub fn determinacy_report(graph: &TemporalGraph, k: usize, target: &[bool]) -> Vec<bool> {
+    let owner: Vec<bool> = graph.node_ownership();
+    let spoils = backward_fixpoint(graph, k, bitset::not(target), |spoils, graph, i| {
+        losing_step(graph, &owner, true, spoils, i)
+    });
+
+    debug_assert!(
+        {
+            let wins = reachable_at(graph, k, true, target);
+            graph.nodes().all(|n| wins[n] != spoils[n])
+        },
+        "determinacy violated: the winning regions of the players do not partition the nodes"
+    );
+
+    bitset::not(&spoils)
+}
+
+// one step of the backward induction for the opponent's safety objective,
+// from the losing region at time `time + 1` to the one at time `time`
+fn losing_step(
+    graph: &TemporalGraph,
+    owner: &[bool],
+    player: bool,
+    loses_at: &[bool],
+    time: usize,
+) -> Vec<bool> {
+    let mut loses_before: Vec<bool> = vec![false; graph.node_count];
+    for node in graph.nodes() {
+        loses_before[node] = match owner[node] == player {
+            // the player cannot avoid the losing region (or is stuck)
+            true => graph.successors_at(node, time).all(|s| loses_at[s]),
+            // the opponent can stay in the losing region (or is stuck)
+            false => {
+                graph.successors_at(node, time).next().is_none()
+                    || graph.successors_at(node, time).any(|s| loses_at[s])
+            }
+        }
+    }
+    loses_before
+}
+
+/// Returns a play from `start` in which the opponent keeps `player` from reaching
+/// the target at time k, or `None` if `start` is winning for `player`.
+///
+/// Entry `i` of the play is the node at time `i`. The opponent follows a winning
+/// strategy; the player takes its first available edge (all of them are losing).
+/// The play ends early if it gets stuck in a node without available edges.
+pub fn spoiling_strategy(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+    start: Node,
+) -> Option<Vec<Node>> {
+    let owner: Vec<bool> = graph.node_ownership();
+
+    // loses[i] is the losing region at time i, computed backwards from time k
+    let mut loses: Vec<Vec<bool>> = vec![target.iter().map(|t| !t).collect()];
+    for i in (0..k).rev() {
+        let before = losing_step(graph, &owner, player, loses.last().unwrap(), i);
+        loses.push(before);
+    }
+    loses.reverse();
+    if !loses[0][start] {
+        return None;
+    }
+
+    let mut play = vec![start];
+    let mut node = start;
+    for (i, loses_next) in loses.iter().enumerate().skip(1) {
+        let mut successors = graph.successors_at(node, i - 1);
+        let next = match owner[node] == player {
+            true => successors.next(),
+            false => successors.find(|&s| loses_next[s]),
+        };
+        match next {
+            Some(next) => node = next,
+            None => break,
+        }
+        play.push(node);
+    }
+    Some(play)
+}
+
+/// How strategy extraction chooses among several winning successors of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrategyPreference {
+    /// The winning successor with the lowest node index.
+    #[default]
+    LowestIndex,
+    /// The winning successor with the highest node index.
+    HighestIndex,
+    /// The target of the first winning edge, in the order the edges were added.
+    FirstAvailable,
+}
+
+/// Computes the reachable set at time 0 like [`reachable_at`], together with a winning
+/// strategy for `player`.
+///
+/// # Returns
+/// The winning set at time 0, and the strategy: entry `[i][n]` is the successor that
+/// `player` moves to from node `n` at time `i`, for every node `n` owned by `player`
+/// that is winning at time `i`, and `None` for all other nodes.
+/// The strategy has `k` entries, for the times `0..k`.
+pub fn reachable_at_with_strategy(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+    preference: StrategyPreference,
+) -> (Vec<bool>, Vec<Vec<Option<Node>>>) {
+    let owner: Vec<bool> = graph.node_ownership();
+    let mut wins = winning_at_each_step(graph, k, player, target);
+
+    let strategy = (0..k)
+        .map(|i| {
+            graph
+                .nodes()
+                .map(|n| {
+                    if owner[n] != player || !wins[i][n] {
+                        return None;
+                    }
+                    let mut winning = graph.successors_at(n, i).filter(|&s| wins[i + 1][s]);
+                    match preference {
+                        StrategyPreference::LowestIndex => winning.min(),
+                        StrategyPreference::HighestIndex => winning.max(),
+                        StrategyPreference::FirstAvailable => winning.next(),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    (wins.swap_remove(0), strategy)
+}
+
+/// Returns the edges `(from, to)` that the winning strategy of `player` takes,
+/// at any time `0..k`, from any winning node owned by `player`.
+///
+/// The strategy is the one of [`reachable_at_with_strategy`] with the default
+/// [`StrategyPreference`]. Edges that are never taken are not needed for `player` to win,
+/// although a different strategy might use them.
+pub fn strategy_edge_coverage(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+) -> HashSet<(Node, Node)> {
+    let (_, strategy) =
+        reachable_at_with_strategy(graph, k, player, target, StrategyPreference::default());
+    strategy
+        .iter()
+        .flat_map(|moves| {
+            moves
+                .iter()
+                .enumerate()
+                .filter_map(|(n, next)| next.map(|s| (n, s)))
+        })
+        .collect()
+}
+
+/// The largest number of (node, budget) pairs [`reachable_with_budget`] will tabulate.
+pub const MAX_BUDGET_TABLE_SIZE: usize = 1 << 26;
+
+/// Computes the reachable set at time 0 for a punctual reachability game in which
+/// every edge has a weight (energy cost), and a play is only valid as long as the running
+/// budget, starting at `start_budget`, never drops below zero.
+///
+/// The backward induction ranges over pairs of node and budget. Budgets are capped at
+/// `k` times the largest cost (negative weight): a budget of at least that can never drop
+/// below zero within `k` steps, so all larger budgets are equivalent to it.
+/// They are also capped at `start_budget` plus `k` times the largest gain, since no
+/// play can collect more than that.
+/// An opponent move that would exhaust the budget makes the reaching player lose.
+///
+/// # Returns
+/// A vector of booleans indicating which nodes are winning at time 0 with `start_budget`,
+/// which is empty of winning nodes if `start_budget` is negative.
+/// Returns an error if the table of nodes and budgets would have more than
+/// [`MAX_BUDGET_TABLE_SIZE`] entries.
+pub fn reachable_with_budget(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target: &[bool],
+    start_budget: i64,
+) -> Result<Vec<bool>, String> {
+    if start_budget < 0 {
+        return Ok(vec![false; graph.node_count]);
+    }
+    let owner: Vec<bool> = graph.node_ownership();
+    let steps = i64::try_from(k).unwrap_or(i64::MAX);
+    let max_loss = graph
+        .edges()
+        .map(|e| e.weight().saturating_neg())
+        .max()
+        .unwrap_or(0)
+        .max(0);
+    let max_gain = graph.edges().map(|e| e.weight()).max().unwrap_or(0).max(0);
+    let cap = steps
+        .saturating_mul(max_loss)
+        .min(start_budget.saturating_add(steps.saturating_mul(max_gain)));
+    let start_budget = start_budget.min(cap);
+
+    let table_size = usize::try_from(cap)
+        .ok()
+        .and_then(|cap| cap.checked_add(1))
+        .and_then(|budgets| budgets.checked_mul(graph.node_count.max(1)))
+        .filter(|&size| size <= MAX_BUDGET_TABLE_SIZE);
+    if table_size.is_none() {
+        return Err(format!(
+            "budgets up to {} over {} nodes exceed the limit of {} table entries",
+            cap, graph.node_count, MAX_BUDGET_TABLE_SIZE
+        ));
+    }
+
+    // wins_at[b][n]: node n wins with budget b
+    let mut wins_at: Vec<Vec<bool>> = vec![target.to_vec(); cap as usize + 1];
+    let mut wins_before: Vec<Vec<bool>> = vec![vec![false; graph.node_count]; cap as usize + 1];
+
+    for i in (0..k).rev() {
+        for (budget, wins_before) in wins_before.iter_mut().enumerate() {
+            // whether taking an edge with the budget leads to a winning state
+            let good = |weight: i64, next: Node| {
+                let b = (budget as i64).saturating_add(weight);
+                b >= 0 && wins_at[b.min(cap) as usize][next]
+            };
+            for node in graph.nodes() {
+                let mut edges = graph.edges_from_at(node, i).peekable();
+                wins_before[node] = match owner[node] == player {
+                    true => edges.any(|e| good(e.weight(), *e.target())),
+                    false => edges.peek().is_some() && edges.all(|e| good(e.weight(), *e.target())),
+                }
+            }
+        }
+        std::mem::swap(&mut wins_at, &mut wins_before);
+    }
+
+    Ok(wins_at.swap_remove(start_budget as usize))
+}
+
+/// Returns the number of nodes in a winning set.
+pub fn winning_count(winning: &[bool]) -> usize {
+    winning.iter().filter(|&&w| w).count()
+}
+
+/// The controllable predecessor operator: one step of the backward induction.
+///
+/// Returns the set of nodes from which `player` can force the play into `wins`
+/// by taking an edge available at time `time`.
+/// Nodes owned by `player` need some available successor in `wins`;
+/// opponent nodes need at least one available successor, all of which are in `wins`.
+///
+/// A self-loop counts like any other edge: an available self-loop lets the player
+/// win a node that is in `wins`, and lets the opponent spoil a node that is not,
+/// whatever the node's other edges.
+///
+/// # Arguments
+/// * `graph` - Reference to the temporal graph
+/// * `owner` - node ownership, as returned by `graph.node_ownership()`
+/// * `player` - Boolean player who wants to reach
+/// * `wins` - the winning set at time `time + 1`
+/// * `time` - the time at which edges are taken
+pub fn cpre(
+    graph: &TemporalGraph,
+    owner: &[bool],
+    player: bool,
+    wins: &[bool],
+    time: usize,
+) -> Vec<bool> {
+    cpre_by(
+        owner,
+        player,
+        wins,
+        DeadlockPolicy::OpponentWins,
+        &[],
+        |node| graph.successors_at(node, time),
+    )
+}
+
+// `cpre` for an arbitrary successor function.
+//
+// A self-loop lets the owner of a node stay put. For the reaching player that is one
+// more way to win: a node with a self-loop wins if it is itself winning one step later.
+// For the opponent it is one more way to spoil: a node with a self-loop is losing
+// unless it is itself winning one step later, whatever its other edges.
+// `self_loops[n]` (see `TemporalGraph::always_self_loops`; may be empty) marks nodes
+// whose self-loop is available at every time, for which this is decided without
+// iterating over the successors.
+fn cpre_by<I: Iterator<Item = Node>>(
+    owner: &[bool],
+    player: bool,
+    wins: &[bool],
+    deadlock: DeadlockPolicy,
+    self_loops: &[bool],
+    successors: impl Fn(Node) -> I,
+) -> Vec<bool> {
+    let mut wins_before: Vec<bool> = vec![false; owner.len()];
+    for (node, w) in wins_before.iter_mut().enumerate() {
+        let stay = self_loops.get(node).copied().unwrap_or(false);
+        *w = match owner[node] == player {
+            true if stay && wins[node] => true,
+            false if stay && !wins[node] => false,
+            true => successors(node).any(|s| wins[s]),
+            false => match successors(node).next() {
+                None => deadlock == DeadlockPolicy::OpponentLoses,
+                Some(_) => successors(node).all(|s| wins[s]),
+            },
+        }
+    }
+    wins_before
+}
+
+/// Computes the Büchi winning region: the nodes from which `player` can force
+/// visiting the target set infinitely often.
+///
+/// Büchi objectives are an infinite-horizon notion, so this uses the static graph:
+/// every edge is considered available, ignoring the punctual time index.
+/// It is the nested fixpoint `νZ. μY. (target ∩ cpre(Z)) ∪ cpre(Y)`.
+pub fn buchi(graph: &TemporalGraph, player: bool, target: &[bool]) -> Vec<bool> {
+    let owner: Vec<bool> = graph.node_ownership();
+    let cpre_static = |set: &[bool]| {
+        cpre_by(
+            &owner,
+            player,
+            set,
+            DeadlockPolicy::OpponentWins,
+            &[],
+            |n| graph.successors(n),
+        )
+    };
+
+    let mut z: Vec<bool> = vec![true; graph.node_count];
+    loop {
+        // the target nodes from which the play can be forced back into z
+        let recur: Vec<bool> = cpre_static(&z)
+            .iter()
+            .zip(target)
+            .map(|(&c, &t)| c && t)
+            .collect();
+
+        // the nodes that can force reaching those
+        let mut y: Vec<bool> = vec![false; graph.node_count];
+        loop {
+            let y_next: Vec<bool> = cpre_static(&y)
+                .iter()
+                .zip(&recur)
+                .map(|(&c, &r)| c || r)
+                .collect();
+            if y_next == y {
+                break;
+            }
+            y = y_next;
+        }
+
+        if y == z {
+            return z;
+        }
+        z = y;
+    }
+}
+
+/// Computes the winning sets at time 0 for every horizon `0..=max_k`.
+///
+/// Edge availability at each time is evaluated only once and shared between
+/// the backward inductions for the different horizons.
+///
+/// # Returns
+/// A vector whose entry `k` is the winning set at time 0 for horizon `k`,
+/// i.e. `reachable_at(graph, k, player, target)`.
+pub fn reachable_all(
+    graph: &TemporalGraph,
+    max_k: usize,
+    player: bool,
+    target: &[bool],
+) -> Vec<Vec<bool>> {
+    reachable_all_with_progress(graph, max_k, player, target, |_| {})
+}
+
+/// Computes the winning sets for every horizon like [`reachable_all`],
+/// calling `on_step(k)` after the winning set for horizon `k` is computed.
+pub fn reachable_all_with_progress(
+    graph: &TemporalGraph,
+    max_k: usize,
+    player: bool,
+    target: &[bool],
+    mut on_step: impl FnMut(usize),
+) -> Vec<Vec<bool>> {
+    let owner: Vec<bool> = graph.node_ownership();
+
+    // successors[i][n] are the successors of node n at time i
+    let successors = graph.precompute_successors(max_k);
+    let self_loops = graph.always_self_loops();
+
+    (0..=max_k)
+        .map(|k| {
+            let wins_at = backward_fixpoint(graph, k, target.to_vec(), |wins_at, _, i| {
+                cpre_by(
+                    &owner,
+                    player,
+                    wins_at,
+                    DeadlockPolicy::OpponentWins,
+                    &self_loops,
+                    |n| successors[i][n].iter().copied(),
+                )
+            });
+            on_step(k);
+            wins_at
+        })
+        .collect()
+}
+
+/// Computes the winning set at time 0 when each target node has its own deadline.
+///
+/// A node `n` with `targets[n] == Some(d)` counts as reached if the play
+/// is at `n` at exactly time `d`; nodes with `None` are never targets.
+/// The backward induction starts at the maximal deadline and injects each
+/// target into the winning set at its own step.
+///
+/// # Arguments
+/// * `graph` - Reference to the temporal graph
+/// * `player` - Boolean player who wants to reach
+/// * `targets` - a deadline per node (or `None` if not a target)
+///
+/// # Returns
+/// A vector of booleans indicating which nodes are in the winning set at time 0
+pub fn reachable_multi_deadline(
+    graph: &TemporalGraph,
+    player: bool,
+    targets: &[Option<usize>],
+) -> Vec<bool> {
+    let owner: Vec<bool> = graph.node_ownership();
+    let k = targets.iter().flatten().copied().max().unwrap_or(0);
+
+    let target_at = |i: usize| -> Vec<bool> { targets.iter().map(|d| *d == Some(i)).collect() };
+
+    backward_fixpoint(graph, k, target_at(k), |wins_at, graph, i| {
+        let mut wins_before = cpre(graph, &owner, player, wins_at, i);
+        for (w, t) in wins_before.iter_mut().zip(target_at(i)) {
+            *w |= t;
+        }
+        wins_before
+    })
+}
+
+/// Computes the winning set at time 0 for reaching the target at some time in the
+/// window `a..=b`, rather than at exactly one time.
+///
+/// This generalises [`reachable_at`] (`a == b`) and reaching the target by time `b`
+/// (`a == 0`). The backward induction starts at `b` and adds the target to the
+/// winning set at every step within the window. An empty window (`a > b`)
+/// is won from nowhere.
+///
+/// # Arguments
+/// * `graph` - Reference to the temporal graph
+/// * `a` - The first time at which the target counts as reached
+/// * `b` - The last time at which the target counts as reached
+/// * `player` - Boolean player who wants to reach
+/// * `target` - target set
+pub fn reachable_in_window(
+    graph: &TemporalGraph,
+    a: usize,
+    b: usize,
+    player: bool,
+    target: &[bool],
+) -> Vec<bool> {
+    if a > b {
+        return vec![false; graph.node_count];
+    }
+    let owner: Vec<bool> = graph.node_ownership();
+
+    backward_fixpoint(graph, b, target.to_vec(), |wins_at, graph, i| {
+        let mut wins_before = cpre(graph, &owner, player, wins_at, i);
+        if i >= a {
+            for (w, &t) in wins_before.iter_mut().zip(target) {
+                *w |= t;
+            }
+        }
+        wins_before
+    })
+}
+
+/// Computes the winning set at time 0 like [`reachable_at`], for a target set that
+/// changes over time: `target_at(i)` is the target set at time `i`.
+///
+/// Under punctual semantics only the time `k` matters, so only `target_at(k)` is
+/// evaluated; whether a node is a target at other times has no influence on the
+/// result. To count visits at earlier times as well, see [`reachable_in_window`]
+/// and [`reachable_multi_deadline`].
+pub fn reachable_at_timed_target(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    target_at: impl Fn(usize) -> Vec<bool>,
+) -> Vec<bool> {
+    reachable_at(graph, k, player, &target_at(k))
+}
+
+/// Plain (one-player) reachability forward in time from `start`, without an adversary.
+/// Starting at `start` at time 0, a step taken at time `i` may use any edge available at `i`.
+///
+/// # Returns
+/// If `punctual`, the nodes that can be occupied at exactly time `k`;
+/// otherwise the nodes that can be occupied at some time in `0..=k`.
+pub fn forward_reachable(
+    graph: &TemporalGraph,
+    start: Node,
+    k: usize,
+    punctual: bool,
+) -> Vec<bool> {
+    let mut current = vec![false; graph.node_count];
+    current[start] = true;
+    let mut seen = current.clone();
+
+    for i in 0..k {
+        let mut next = vec![false; graph.node_count];
+        for n in graph.nodes().filter(|&n| current[n]) {
+            for m in graph.successors_at(n, i) {
+                next[m] = true;
+            }
+        }
+        for (s, &n) in seen.iter_mut().zip(&next) {
+            *s |= n;
+        }
+        current = next;
+    }
+
+    if punctual { current } else { seen }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formulae::Formula;
+    use crate::parser::NodeAttr;
+    use crate::temporal_graphs::Edge;
+
+    // Helper function to create a single-state graph owned by player 0 with a self-loop
+    // Creates: s0 (player 0) with self-loop edge that is always available (constraint "true")
+    fn create_self_loop() -> TemporalGraph {
+        let node_count = 1;
+
+        // Create node ID mapping
+        let mut node_id_map = HashMap::new();
+        node_id_map.insert("s0".to_string(), 0);
+
+        // Create node attributes
+        let mut node_attrs = HashMap::new();
+        let mut s0_attrs = HashMap::new();
+        s0_attrs.insert("owner".to_string(), NodeAttr::Owner(false)); // player 0
+        s0_attrs.insert("label".to_string(), NodeAttr::Label("s0".to_string()));
+        node_attrs.insert(0, s0_attrs);
+
+        // Create self-loop edge with constraint "true"
+        let edges = vec![Edge::new(0, 0, Formula::True)];
+
+        TemporalGraph::new(node_count, node_id_map, node_attrs, edges)
+    }
+
+    // Helper: two-state graph, both with self-loops (constraint true),
+    // and state 0 has an edge to state 1 with constraint x >= 5.
+    fn create_two_state_graph() -> TemporalGraph {
+        let node_count = 2;
+        let mut node_id_map = HashMap::new();
+        node_id_map.insert("s0".to_string(), 0);
+        node_id_map.insert("s1".to_string(), 1);
+
+        let mut node_attrs = HashMap::new();
+        let mut s0_attrs = HashMap::new();
+        s0_attrs.insert("owner".to_string(), NodeAttr::Owner(false));
+        s0_attrs.insert("label".to_string(), NodeAttr::Label("s0".to_string()));
+        node_attrs.insert(0, s0_attrs);
+        let mut s1_attrs = HashMap::new();
+        s1_attrs.insert("owner".to_string(), NodeAttr::Owner(false));
+        s1_attrs.insert("label".to_string(), NodeAttr::Label("s1".to_string()));
+        node_attrs.insert(1, s1_attrs);
+
+        use crate::formulae::{Expr, Formula};
+        let edges = vec![
+            // self-loops
+            Edge::new(0, 0, Formula::True),
+            Edge::new(1, 1, Formula::True),
+            // edge from 0 to 1 with constraint x >= 5
+            Edge::new(
+                0,
+                1,
+                Formula::Ge(
+                    Box::new(Expr::Var("x".to_string())),
+                    Box::new(Expr::Const(5)),
+                ),
+            ),
+        ];
+        TemporalGraph::new(node_count, node_id_map, node_attrs, edges)
+    }
+
+    // Helper: the graph of figure 2 in DOI:10.1007/978-3-031-57228-9_5,
+    // whose edge availability has period 2.
+    // s (player true) -> u at even times, u (player false) -> t, tt at odd times,
+    // t -> t, t -> s always; tt has no outgoing edges.
+    fn create_period_two_graph() -> TemporalGraph {
+        use crate::formulae::Expr;
+
+        let mut node_id_map = HashMap::new();
+        let mut node_attrs = HashMap::new();
+        for (idx, (id, owner)) in [("s", true), ("u", false), ("t", true), ("tt", true)]
+            .into_iter()
+            .enumerate()
+        {
+            node_id_map.insert(id.to_string(), idx);
+            let mut attrs = HashMap::new();
+            attrs.insert("owner".to_string(), NodeAttr::Owner(owner));
+            node_attrs.insert(idx, attrs);
+        }
+
+        let parity = |r| {
+            Formula::Eq(
+                Box::new(Expr::Mod(Box::new(Expr::Var("t".to_string())), 2)),
+                Box::new(Expr::Const(r)),
+            )
+        };
+        let edges = vec![
+            Edge::new(0, 1, parity(0)),
+            Edge::new(1, 2, parity(1)),
+            Edge::new(1, 3, parity(1)),
+            Edge::new(2, 2, Formula::True),
+            Edge::new(2, 0, Formula::True),
+        ];
+        TemporalGraph::new(4, node_id_map, node_attrs, edges)
+    }
+
+    #[test]
+    fn test_self_loop_0() {
+        let graph = create_self_loop();
+
+        // Target the single state (node 0) at time 0
+        let target = vec![true]; // node 0 is the target
+        let k = 0;
+
+        assert_eq!(reachable_at(&graph, k, true, &target), vec![true]);
+        assert_eq!(reachable_at(&graph, k, false, &target), vec![true]);
+    }
+
+    #[test]
+    fn test_self_loop_1() {
+        let graph = create_self_loop();
+
+        // Target the single state (node 0) at time 0
+        let target = vec![true]; // node 0 is the target
+        let k = 1;
+
+        assert_eq!(reachable_at(&graph, k, true, &target), vec![true]);
+        assert_eq!(reachable_at(&graph, k, false, &target), vec![true]);
+    }
+
+    #[test]
+    fn test_reachable_at_on_fixtures() {
+        use crate::parser::TemporalGraphParser;
+
+        for input in [
+            include_str!("../tests/fixtures/two_state.tg"),
+            include_str!("../tests/fixtures/labeled.tg"),
+            include_str!("../tests/fixtures/numbered.tg"),
+            include_str!("../tests/fixtures/meta.tg"),
+            include_str!("../tests/fixtures/batch/a.tg"),
+            include_str!("../tests/fixtures/batch/b.tg"),
+        ] {
+            let graph = TemporalGraphParser::new().parse(input).unwrap();
+            let owner = graph.node_ownership();
+            for node in graph.nodes() {
+                let mut target = vec![false; graph.node_count];
+                target[node] = true;
+                for player in [true, false] {
+                    for k in 0..12 {
+                        // the plain backward induction, one step at a time
+                        let mut expected = target.clone();
+                        for i in (0..k).rev() {
+                            expected = cpre(&graph, &owner, player, &expected, i);
+                        }
+                        assert_eq!(reachable_at(&graph, k, player, &target), expected);
+                        let fixpoint = backward_fixpoint(&graph, k, target.clone(), |w, g, i| {
+                            cpre(g, &owner, player, w, i)
+                        });
+                        assert_eq!(fixpoint, expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_backward_fixpoint_custom_step() {
+        let graph = create_window_graph();
+        let owner = graph.node_ownership();
+        let target = vec![false, true];
+
+        // reaching the target by time 4, by adding it back at every step
+        let by = backward_fixpoint(&graph, 4, target.clone(), |wins_at, graph, i| {
+            bitset::or(&cpre(graph, &owner, true, wins_at, i), &target).unwrap()
+        });
+        assert_eq!(by, reachable_in_window(&graph, 0, 4, true, &target));
+        assert_eq!(by, vec![true, true]);
+
+        // no steps
+        let init = vec![true, false];
+        assert_eq!(
+            backward_fixpoint(&graph, 0, init.clone(), |_, _, _| unreachable!()),
+            init
+        );
+    }
+
+    #[test]
+    fn test_two_state_winning_set() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+
+        let winning = reachable_at_set(&graph, 6, false, &target);
+        assert_eq!(winning.as_slice(), reachable_at(&graph, 6, false, &target));
+        assert!(winning.contains(0) && winning.contains(1));
+        assert!(!winning.contains(2));
+        assert_eq!(winning.count(), 2);
+        assert_eq!(winning.node_count(), 2);
+        assert_eq!(winning.iter_winning_nodes().collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(
+            winning.ids(&graph),
+            HashSet::from(["s0".to_string(), "s1".to_string()])
+        );
+
+        // the edge 0 --> 1 is not yet available in time
+        let winning = reachable_at_set(&graph, 5, false, &target);
+        assert!(!winning.contains(0) && winning.contains(1));
+        assert_eq!(winning.count(), 1);
+        assert_eq!(winning.iter_winning_nodes().collect::<Vec<_>>(), [1]);
+        assert_eq!(winning.ids(&graph), HashSet::from(["s1".to_string()]));
+        assert_eq!(Vec::from(winning), [false, true]);
+    }
+
+    #[test]
+    fn test_two_state_without_self_loops() {
+        let graph = create_two_state_graph();
+        let moving = graph.without_self_loops();
+        assert_eq!(moving.edge_count(), 1);
+        let target = vec![false, true];
+
+        // state 0 can no longer wait for the edge to state 1, and state 1 is stuck
+        assert_eq!(reachable_at(&graph, 6, false, &target), vec![true, true]);
+        assert_eq!(reachable_at(&moving, 6, false, &target), vec![false, false]);
+        // only the target itself is winning without moving
+        assert_eq!(reachable_at(&moving, 0, false, &target), vec![false, true]);
+        // the winning region only shrinks
+        for k in 0..8 {
+            let wins = reachable_at(&graph, k, false, &target);
+            let moving_wins = reachable_at(&moving, k, false, &target);
+            assert!(moving_wins.iter().zip(&wins).all(|(m, w)| !m || *w));
+        }
+    }
+
+    #[test]
+    fn test_strategy_edge_coverage() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+
+        // state 0 waits, then takes the edge to state 1 at time 5
+        for k in 6..9 {
+            let coverage = strategy_edge_coverage(&graph, k, false, &target);
+            assert!(coverage.contains(&(0, 1)));
+            assert_eq!(coverage, HashSet::from([(0, 0), (0, 1), (1, 1)]));
+        }
+        // too early for the edge: state 1 only waits
+        assert_eq!(
+            strategy_edge_coverage(&graph, 5, false, &target),
+            HashSet::from([(1, 1)])
+        );
+        // the reaching player owns no node
+        assert!(strategy_edge_coverage(&graph, 6, true, &target).is_empty());
+    }
+
+    #[test]
+    fn test_two_state_reachability() {
+        let graph = create_two_state_graph();
+
+        // Let state 1 be the only target
+        let target = vec![false, true];
+
+        // assume perspective of player false
+        let reacher = false;
+
+        // player false can force to reach the target at time 0 only from the target
+        assert_eq!(reachable_at(&graph, 0, reacher, &target), vec![false, true]);
+        // player false can force to reach the target at times 1-4 only from the target
+        assert_eq!(reachable_at(&graph, 1, reacher, &target), vec![false, true]);
+        assert_eq!(reachable_at(&graph, 2, reacher, &target), vec![false, true]);
+        assert_eq!(reachable_at(&graph, 3, reacher, &target), vec![false, true]);
+        assert_eq!(reachable_at(&graph, 4, reacher, &target), vec![false, true]);
+
+        // player false can force to reach the target at times 5 only from the target,
+        // because it would have to take the edge 0 --> 1 at time 4;
+        // it is only available from time 5 onwards.
+
+        assert_eq!(reachable_at(&graph, 5, reacher, &target), vec![false, true]);
+
+        // player false CAN force to reach the target at time 6 and later
+        // from states 1 (target) AND 0
+        // (by wating at 0 and then taking edge 0 --> 1 at time 5)
+        assert_eq!(reachable_at(&graph, 6, reacher, &target), vec![true, true]);
+        assert_eq!(reachable_at(&graph, 7, reacher, &target), vec![true, true]);
+
+        // player !reacher == true (the opponent here) can force to reach the
+        // target only from the target, no matter when, because she does not control the edges (own
+        // state 0 in particular)
+        assert_eq!(
+            reachable_at(&graph, 7, !reacher, &target),
+            vec![false, true]
+        );
+    }
+
+    #[test]
+    fn test_multi_deadline_single_target_matches_reachable_at() {
+        let graph = create_two_state_graph();
+
+        // only state 1 is a target, with deadline 6
+        let targets = vec![None, Some(6)];
+        assert_eq!(
+            reachable_multi_deadline(&graph, false, &targets),
+            reachable_at(&graph, 6, false, &[false, true])
+        );
+    }
+
+    #[test]
+    fn test_multi_deadline_differs_from_single_deadline() {
+        let graph = create_two_state_graph();
+
+        // with a single deadline 5, only the target state 1 wins
+        assert_eq!(
+            reachable_at(&graph, 5, false, &[false, true]),
+            vec![false, true]
+        );
+
+        // state 0 is a target at time 2, state 1 at time 5:
+        // now state 0 wins by waiting on its self-loop until time 2
+        let targets = vec![Some(2), Some(5)];
+        assert_eq!(
+            reachable_multi_deadline(&graph, false, &targets),
+            vec![true, true]
+        );
+
+        // state 1 as a target at time 2 is not reachable from state 0
+        let targets = vec![None, Some(2)];
+        assert_eq!(
+            reachable_multi_deadline(&graph, false, &targets),
+            vec![false, true]
+        );
+    }
+
+    // s0 waits, and can move to s1 only at time 2; s1 must move back to s0
+    fn create_window_graph() -> TemporalGraph {
+        use crate::formulae::Expr;
+        use crate::temporal_graphs::TemporalGraphBuilder;
+        TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", true, None)
+            .edge("s0", "s0", Formula::True)
+            .edge("s1", "s0", Formula::True)
+            .edge(
+                "s0",
+                "s1",
+                Formula::Eq(
+                    Box::new(Expr::Var("t".to_string())),
+                    Box::new(Expr::Const(2)),
+                ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reachable_in_window() {
+        let graph = create_window_graph();
+        let target = [false, true];
+
+        // a == b is punctual reachability
+        for k in 0..6 {
+            assert_eq!(
+                reachable_in_window(&graph, k, k, true, &target),
+                reachable_at(&graph, k, true, &target)
+            );
+        }
+        // s1 can only be visited at time 0 (from s1) or 3: not at time 4...
+        assert_eq!(reachable_at(&graph, 4, true, &target), [false, false]);
+        // ...but by time 4
+        assert_eq!(
+            reachable_in_window(&graph, 0, 4, true, &target),
+            [true, true]
+        );
+        // by-time still needs a visit within the window
+        assert_eq!(
+            reachable_in_window(&graph, 0, 2, true, &target),
+            [false, true]
+        );
+        // a window that excludes time 0 but includes time 3
+        assert_eq!(
+            reachable_in_window(&graph, 1, 3, true, &target),
+            [true, true]
+        );
+        assert_eq!(
+            reachable_in_window(&graph, 1, 2, true, &target),
+            [false, false]
+        );
+        assert_eq!(
+            reachable_in_window(&graph, 4, 6, true, &target),
+            [false, false]
+        );
+        // an empty window
+        assert_eq!(
+            reachable_in_window(&graph, 3, 2, true, &target),
+            [false, false]
+        );
+    }
+
+    #[test]
+    fn test_reachable_at_capped() {
+        use crate::formulae::Expr;
+        use crate::temporal_graphs::TemporalGraphBuilder;
+
+        // s0 and s1 alternate, and s0 may leave to the sink s2 until time 2:
+        // the winning sets keep oscillating after the horizon
+        let alternating = TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", false, None)
+            .node("s2", false, None)
+            .edge("s0", "s1", Formula::True)
+            .edge("s1", "s0", Formula::True)
+            .edge("s2", "s2", Formula::True)
+            .edge(
+                "s0",
+                "s2",
+                Formula::Le(
+                    Box::new(Expr::Var("t".to_string())),
+                    Box::new(Expr::Const(2)),
+                ),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(alternating.max_meaningful_time(), Some(3));
+        assert_ne!(
+            reachable_at(&alternating, 20, true, &[true, false, false]),
+            reachable_at(&alternating, 21, true, &[true, false, false])
+        );
+
+        for graph in [create_window_graph(), alternating] {
+            let horizon = graph.max_meaningful_time().unwrap();
+            let n = graph.node_count;
+            // every nonempty target set
+            for bits in 1..(1 << n) {
+                let target: Vec<bool> = (0..n).map(|i| bits & (1 << i) != 0).collect();
+                for player in [true, false] {
+                    for k in 0..30 {
+                        assert_eq!(
+                            reachable_at_capped(&graph, k, player, &target, horizon),
+                            reachable_at(&graph, k, player, &target),
+                            "k = {}, player = {}, target = {:?}",
+                            k,
+                            player,
+                            target
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reachable_at_timed_target() {
+        let graph = create_window_graph();
+        // s1 is a target only at even times, s0 at all others
+        let target_at = |i: usize| vec![!i.is_multiple_of(2), i.is_multiple_of(2)];
+        let asked = std::cell::RefCell::new(Vec::new());
+        let recording = |i: usize| {
+            asked.borrow_mut().push(i);
+            target_at(i)
+        };
+
+        // at time 4 only s1 is a target, and it cannot be occupied then
+        assert_eq!(
+            reachable_at_timed_target(&graph, 4, true, recording),
+            [false, false]
+        );
+        assert_eq!(*asked.borrow(), [4]);
+        // at time 3 only s0 is a target, where both nodes can wait
+        assert_eq!(
+            reachable_at_timed_target(&graph, 3, true, target_at),
+            [true, true]
+        );
+        for k in 0..6 {
+            assert_eq!(
+                reachable_at_timed_target(&graph, k, true, target_at),
+                reachable_at(&graph, k, true, &target_at(k))
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_deadline_no_targets() {
+        let graph = create_two_state_graph();
+        assert_eq!(
+            reachable_multi_deadline(&graph, false, &[None, None]),
+            vec![false, false]
+        );
+    }
+
+    #[test]
+    fn test_avoiding_forbidden_target() {
+        let graph = create_two_state_graph();
+
+        // state 1 is both the target and forbidden: nobody wins
+        let target = vec![false, true];
+        let avoid = vec![false, true];
+        for k in [0, 1, 6, 7] {
+            assert_eq!(
+                reachable_at_avoiding(&graph, k, false, &target, &avoid),
+                vec![false, false]
+            );
+        }
+    }
+
+    #[test]
+    fn test_avoiding_nothing_matches_reachable_at() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+        let avoid = vec![false, false];
+        for k in 0..8 {
+            assert_eq!(
+                reachable_at_avoiding(&graph, k, false, &target, &avoid),
+                reachable_at(&graph, k, false, &target)
+            );
+        }
+    }
+
+    #[test]
+    fn test_avoiding_start_node() {
+        let graph = create_two_state_graph();
+
+        // forbidding state 0 makes it losing even though it could reach state 1
+        let target = vec![false, true];
+        let avoid = vec![true, false];
+        assert_eq!(
+            reachable_at_avoiding(&graph, 6, false, &target, &avoid),
+            vec![false, true]
+        );
+    }
+
+    #[test]
+    fn test_periodic_matches_reachable_at() {
+        let graph = create_period_two_graph();
+        assert_eq!(graph.availability_period(), Some(2));
+
+        for target in [
+            vec![false, false, true, false],
+            vec![true, false, false, false],
+            vec![false, false, false, true],
+        ] {
+            for player in [true, false] {
+                for k in 0..40 {
+                    assert_eq!(
+                        reachable_at_periodic(&graph, k, player, &target),
+                        reachable_at(&graph, k, player, &target),
+                        "target {:?}, player {}, k {}",
+                        target,
+                        player,
+                        k
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_periodic_with_shifted_mod() {
+        use crate::parser::TemporalGraphParser;
+
+        // `(mod (- t 5) 3)` truncates towards zero, so its residues below t = 5
+        // do not follow the pattern above, and the cycle skip must not be used
+        let graph = TemporalGraphParser::new()
+            .parse(
+                "node x: owner[0]
+                 node a: owner[0]
+                 node b: owner[0]
+                 node s: owner[0]
+                 edge x -> a
+                 edge a -> b: (= (mod (- t 5) 3) 2)
+                 edge a -> s
+                 edge b -> b
+                 edge s -> s",
+            )
+            .unwrap();
+        assert_eq!(graph.availability_period(), None);
+
+        let target = vec![false, true, true, false];
+        for k in [19, 22, 100] {
+            assert_eq!(
+                reachable_at_periodic(&graph, k, true, &target),
+                reachable_at(&graph, k, true, &target),
+                "k {}",
+                k
+            );
+        }
+    }
+
+    #[test]
+    fn test_periodic_huge_horizon() {
+        let graph = create_period_two_graph();
+        let target = vec![true, false, false, false];
+
+        // the winning sets cycle quickly, so a huge horizon is answered
+        // like a small horizon of the same parity
+        assert_eq!(
+            reachable_at_periodic(&graph, 1_000_000, true, &target),
+            reachable_at(&graph, 20, true, &target)
+        );
+        assert_eq!(
+            reachable_at_periodic(&graph, 1_000_001, true, &target),
+            reachable_at(&graph, 21, true, &target)
+        );
+    }
+
+    #[test]
+    fn test_periodic_falls_back_when_not_periodic() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+        for k in 0..8 {
+            assert_eq!(
+                reachable_at_periodic(&graph, k, false, &target),
+                reachable_at(&graph, k, false, &target)
+            );
+        }
+    }
+
+    #[test]
+    fn test_self_loop_huge_horizon() {
+        let graph = create_self_loop();
+        assert!(graph.is_time_invariant());
+
+        // the fixpoint is reached immediately, so this returns without iterating k times
+        let target = vec![true];
+        assert_eq!(
+            reachable_at(&graph, 1_000_000_000_000, true, &target),
+            vec![true]
+        );
+        assert_eq!(
+            reachable_at(&graph, 1_000_000_000_000, false, &target),
+            vec![true]
+        );
+        assert_eq!(
+            reachable_at(&graph, 1_000_000_000_000, true, &[false]),
+            vec![false]
+        );
+    }
+
+    #[test]
+    fn test_early_termination_not_used_for_time_dependent_graph() {
+        use crate::formulae::Expr;
+
+        // two states with self-loops, and an edge 0 -> 1 available only before time 3
+        let mut node_id_map = HashMap::new();
+        node_id_map.insert("s0".to_string(), 0);
+        node_id_map.insert("s1".to_string(), 1);
+        let edges = vec![
+            Edge::new(0, 0, Formula::True),
+            Edge::new(1, 1, Formula::True),
+            Edge::new(
+                0,
+                1,
+                Formula::Lt(
+                    Box::new(Expr::Var("t".to_string())),
+                    Box::new(Expr::Const(3)),
+                ),
+            ),
+        ];
+        let graph = TemporalGraph::new(2, node_id_map, HashMap::new(), edges);
+        assert!(!graph.is_time_invariant());
+
+        // the winning set is unchanged in the last steps before k,
+        // but the edge 0 -> 1 is available earlier, so iteration must go on
+        assert_eq!(
+            reachable_at(&graph, 100, false, &[false, true]),
+            vec![true, true]
+        );
+    }
+
+    #[test]
+    fn test_reachable_all_matches_reachable_at() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+        for player in [true, false] {
+            let all = reachable_all(&graph, 8, player, &target);
+            assert_eq!(all.len(), 9);
+            for (k, wins) in all.iter().enumerate() {
+                assert_eq!(*wins, reachable_at(&graph, k, player, &target));
+            }
+        }
+    }
+
+    #[test]
+    fn test_losing_region() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+
+        // state 0 cannot reach state 1 at time 5, but can at time 6
+        assert_eq!(losing_region(&graph, 5, false, &target), vec![true, false]);
+        assert_eq!(losing_region(&graph, 6, false, &target), vec![false, false]);
+        // the opponent controls state 0 and stays there
+        assert_eq!(losing_region(&graph, 7, true, &target), vec![true, false]);
+
+        // the losing region is the complement of the winning region
+        for player in [true, false] {
+            for k in 0..8 {
+                let wins = reachable_at(&graph, k, player, &target);
+                let loses = losing_region(&graph, k, player, &target);
+                assert!(wins.iter().zip(&loses).all(|(w, l)| w != l));
+            }
+        }
+    }
+
+    #[test]
+    fn test_determinacy_report() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+
+        // player 1 owns state 0 and never takes the edge to state 1
+        assert_eq!(determinacy_report(&graph, 5, &target), vec![false, true]);
+        assert_eq!(determinacy_report(&graph, 6, &target), vec![false, true]);
+
+        for k in 0..8 {
+            let report = determinacy_report(&graph, k, &target);
+            assert_eq!(report, reachable_at(&graph, k, true, &target));
+            assert_eq!(
+                report,
+                bitset::not(&losing_region(&graph, k, true, &target))
+            );
+        }
+    }
+
+    #[test]
+    fn test_losing_region_period_two() {
+        let graph = create_period_two_graph();
+        for target in [
+            vec![true, false, false, false],
+            vec![false, false, false, true],
+        ] {
+            for player in [true, false] {
+                for k in 0..10 {
+                    // panics via the debug assertion if determinacy is violated
+                    losing_region(&graph, k, player, &target);
+                }
+            }
+        }
+    }
+
+    // opponent node s0 whose only edge, to the target s1, is available from time 5
+    fn create_dead_end_graph() -> TemporalGraph {
+        use crate::formulae::Expr;
+        use crate::temporal_graphs::TemporalGraphBuilder;
+        TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", false, None)
+            .edge("s1", "s1", Formula::True)
+            .edge(
+                "s0",
+                "s1",
+                Formula::Ge(
+                    Box::new(Expr::Var("t".to_string())),
+                    Box::new(Expr::Const(5)),
+                ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_deadlock_policy() {
+        let graph = create_dead_end_graph();
+        let target = vec![false, true];
+
+        // s0 has no available edge at time 0, so it is decided by the policy alone
+        for k in 1..8 {
+            let wins =
+                reachable_at_with_policy(&graph, k, false, &target, DeadlockPolicy::OpponentWins);
+            assert_eq!(wins, vec![false, true]);
+            assert_eq!(wins, reachable_at(&graph, k, false, &target));
+            let wins =
+                reachable_at_with_policy(&graph, k, false, &target, DeadlockPolicy::OpponentLoses);
+            assert_eq!(wins, vec![true, true]);
+        }
+
+        // at time 5 s0 is not stuck and is forced into the target
+        let owner = graph.node_ownership();
+        assert_eq!(cpre(&graph, &owner, false, &target, 5), vec![true, true]);
+    }
+
+    #[test]
+    fn test_cached_matches_reachable_at() {
+        for graph in [
+            create_two_state_graph(),
+            create_period_two_graph(),
+            create_dead_end_graph(),
+        ] {
+            let successors = graph.precompute_successors(10);
+            for player in [true, false] {
+                for t in graph.nodes() {
+                    let target: Vec<bool> = graph.nodes().map(|n| n == t).collect();
+                    for k in 0..=10 {
+                        assert_eq!(
+                            reachable_at_cached(&graph, k, player, &target, &successors),
+                            reachable_at(&graph, k, player, &target)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_winning_count() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+        assert_eq!(winning_count(&reachable_at(&graph, 5, false, &target)), 1);
+        assert_eq!(winning_count(&reachable_at(&graph, 6, false, &target)), 2);
+        assert_eq!(winning_count(&[]), 0);
+    }
+
+    #[test]
+    fn test_buchi() {
+        use crate::temporal_graphs::TemporalGraphBuilder;
+
+        // a and b form a cycle through the target b; c is a sink outside the target.
+        // The opponent node d can move to a or c, the player node e to a or c.
+        let graph = TemporalGraphBuilder::new()
+            .node("a", true, None)
+            .node("b", true, None)
+            .node("c", true, None)
+            .node("d", false, None)
+            .node("e", true, None)
+            .edge("a", "b", Formula::True)
+            .edge("b", "a", Formula::True)
+            .edge("c", "c", Formula::True)
+            .edge("d", "a", Formula::True)
+            .edge("d", "c", Formula::True)
+            .edge("e", "a", Formula::True)
+            .edge("e", "c", Formula::True)
+            .build()
+            .unwrap();
+        let target = vec![false, true, false, false, false];
+
+        assert_eq!(
+            buchi(&graph, true, &target),
+            vec![true, true, false, false, true]
+        );
+
+        // visiting the target once is not enough: b without the way back
+        let graph = TemporalGraphBuilder::new()
+            .node("a", true, None)
+            .node("b", true, None)
+            .node("c", true, None)
+            .edge("a", "b", Formula::True)
+            .edge("b", "c", Formula::True)
+            .edge("c", "c", Formula::True)
+            .build()
+            .unwrap();
+        assert_eq!(
+            buchi(&graph, true, &[false, true, false]),
+            vec![false, false, false]
+        );
+    }
+
+    #[test]
+    fn test_spoiling_strategy() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+
+        // both nodes belong to player false: the opponent (true) keeps s0 in its self-loop
+        let play = spoiling_strategy(&graph, 6, true, &target, 0).unwrap();
+        assert_eq!(play, vec![0; 7]);
+
+        // player false wins from s0 at k = 6, and from the target anyway
+        assert_eq!(spoiling_strategy(&graph, 6, false, &target, 0), None);
+        assert_eq!(spoiling_strategy(&graph, 6, true, &target, 1), None);
+
+        // at k = 5 the edge to s1 comes too late: player false's play stays in s0
+        assert_eq!(
+            spoiling_strategy(&graph, 5, false, &target, 0),
+            Some(vec![0; 6])
+        );
+    }
+
+    #[test]
+    fn test_reachable_with_budget() {
+        use crate::temporal_graphs::TemporalGraphBuilder;
+
+        // s0 reaches the target s2 via s1, paying 2 and then getting 1 back;
+        // the opponent node s3 may choose the costly edge to the target
+        let graph = TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", true, None)
+            .node("s2", true, None)
+            .node("s3", false, None)
+            .weighted_edge("s0", "s1", Formula::True, -2)
+            .weighted_edge("s1", "s2", Formula::True, 1)
+            .edge("s2", "s2", Formula::True)
+            .edge("s3", "s2", Formula::True)
+            .weighted_edge("s3", "s2", Formula::True, -1)
+            .build()
+            .unwrap();
+        let target = vec![false, false, true, false];
+
+        assert_eq!(
+            reachable_with_budget(&graph, 2, true, &target, 2).unwrap(),
+            vec![true, true, true, true]
+        );
+        // without budget, s0 cannot pay for its edge, and the opponent at s3 exhausts it
+        assert_eq!(
+            reachable_with_budget(&graph, 2, true, &target, 0).unwrap(),
+            vec![false, true, true, false]
+        );
+        // with a large budget, the weights do not matter
+        for k in 0..4 {
+            for budget in [10, i64::MAX] {
+                assert_eq!(
+                    reachable_with_budget(&graph, k, true, &target, budget).unwrap(),
+                    reachable_at(&graph, k, true, &target)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reachable_with_budget_extreme_weights() {
+        use crate::temporal_graphs::TemporalGraphBuilder;
+
+        let graph = TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", true, None)
+            .weighted_edge("s0", "s1", Formula::True, -3)
+            .weighted_edge("s1", "s1", Formula::True, i64::MAX)
+            .build()
+            .unwrap();
+        let target = vec![false, true];
+        for k in 0..4 {
+            assert_eq!(
+                reachable_with_budget(&graph, k, true, &target, i64::MAX).unwrap(),
+                reachable_at(&graph, k, true, &target)
+            );
+        }
+        assert_eq!(
+            reachable_with_budget(&graph, 2, true, &target, 2).unwrap(),
+            vec![false, true]
+        );
+
+        // without costs a single budget value suffices
+        let graph = TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", true, None)
+            .weighted_edge("s0", "s1", Formula::True, 5)
+            .edge("s1", "s1", Formula::True)
+            .build()
+            .unwrap();
+        assert_eq!(
+            reachable_with_budget(&graph, 1_000, true, &target, i64::MAX).unwrap(),
+            vec![true, true]
+        );
+
+        // a huge cost with a small budget only needs budgets up to the start budget
+        let graph = TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", true, None)
+            .weighted_edge("s0", "s1", Formula::True, -1_000_000_000)
+            .edge("s1", "s1", Formula::True)
+            .build()
+            .unwrap();
+        assert_eq!(
+            reachable_with_budget(&graph, 100, true, &target, 5).unwrap(),
+            vec![false, true]
+        );
+        // a table that cannot be bounded that way is refused instead of allocated
+        assert!(reachable_with_budget(&graph, 100, true, &target, i64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_reachable_at_cancellable() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+
+        let cancel = AtomicBool::new(false);
+        for k in 0..8 {
+            assert_eq!(
+                reachable_at_cancellable(&graph, k, false, &target, &cancel),
+                Some(reachable_at(&graph, k, false, &target))
+            );
+        }
+
+        cancel.store(true, Ordering::Relaxed);
+        assert_eq!(
+            reachable_at_cancellable(&graph, 6, false, &target, &cancel),
+            None
+        );
+        // nothing to do, nothing to cancel
+        assert_eq!(
+            reachable_at_cancellable(&graph, 0, false, &target, &cancel),
+            Some(target)
+        );
+    }
+
+    #[test]
+    fn test_forward_reachable() {
+        let graph = create_two_state_graph();
+        // s0 -> s1 is first taken at time 5, arriving at time 6
+        assert_eq!(forward_reachable(&graph, 0, 6, false), vec![true, true]);
+        assert_eq!(forward_reachable(&graph, 0, 5, false), vec![true, false]);
+        assert_eq!(forward_reachable(&graph, 0, 0, false), vec![true, false]);
+        // both nodes can be occupied at exactly time 6
+        assert_eq!(forward_reachable(&graph, 0, 6, true), vec![true, true]);
+        // s1 has no way back to s0
+        assert_eq!(forward_reachable(&graph, 1, 6, false), vec![false, true]);
+    }
+
+    #[test]
+    fn test_solver_incremental() {
+        use crate::formulae::Expr;
+
+        let target = vec![false, true];
+        let mut solver = Solver::new(create_two_state_graph(), 6, false, &target);
+        assert_eq!(
+            solver.winning(),
+            reachable_at(&create_two_state_graph(), 6, false, &target)
+        );
+
+        let set_constraint = |solver: &mut Solver, bound: Option<i64>| {
+            let edges = solver.graph_mut().edges.get_mut(&0).unwrap();
+            edges.retain(|e| *e.target() != 1);
+            if let Some(bound) = bound {
+                let formula = Formula::Ge(
+                    Box::new(Expr::Var("x".to_string())),
+                    Box::new(Expr::Const(bound)),
+                );
+                edges.push(Edge::new(0, 1, formula));
+            }
+            solver.invalidate_edge(0, 1);
+            solver.resolve().to_vec()
+        };
+
+        // too late to reach s1 by time 6
+        assert_eq!(set_constraint(&mut solver, Some(6)), vec![false, true]);
+        // back to the original constraint
+        assert_eq!(set_constraint(&mut solver, Some(5)), vec![true, true]);
+        // available from the start: same result, only the earlier steps change
+        assert_eq!(set_constraint(&mut solver, Some(0)), vec![true, true]);
+        assert_eq!(solver.winning_at(4), &[true, true]);
+        // no edge at all
+        assert_eq!(set_constraint(&mut solver, None), vec![false, true]);
+
+        let fresh = reachable_at(solver.graph(), 6, false, &target);
+        assert_eq!(solver.winning(), fresh);
+
+        // nothing invalidated, nothing to do
+        assert_eq!(solver.resolve(), fresh);
+    }
+
+    #[test]
+    fn test_winning_at_each_step() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+
+        // s0 can move to s1 at time 5 and wait in s0 until then
+        let wins = winning_at_each_step(&graph, 6, false, &target);
+        assert_eq!(wins.len(), 7);
+        assert_eq!(wins[6], target);
+        for w in &wins[..6] {
+            assert_eq!(w, &[true, true]);
+        }
+        assert_eq!(wins[0], reachable_at(&graph, 6, false, &target));
+
+        // the opponent can always stay in s0
+        let wins = winning_at_each_step(&graph, 6, true, &target);
+        assert!(wins.iter().all(|w| w == &target));
+
+        assert_eq!(winning_at_each_step(&graph, 0, false, &target), [target]);
+    }
+
+    #[test]
+    fn test_opponent_self_loop() {
+        use crate::temporal_graphs::TemporalGraphBuilder;
+
+        // s0 belongs to the opponent and can always move to the target s1
+        let build = |self_loop: bool| {
+            let mut builder = TemporalGraphBuilder::new()
+                .node("s0", false, None)
+                .node("s1", true, None)
+                .edge("s0", "s1", Formula::True)
+                .edge("s1", "s1", Formula::True);
+            if self_loop {
+                builder = builder.edge("s0", "s0", Formula::True);
+            }
+            builder.build().unwrap()
+        };
+        let target = vec![false, true];
+
+        // without a self-loop the opponent is forced into the target
+        let graph = build(false);
+        assert_eq!(reachable_at(&graph, 3, true, &target), vec![true, true]);
+        // with one it can stay in s0 until time 3
+        let graph = build(true);
+        assert_eq!(graph.always_self_loops(), vec![true, true]);
+        assert_eq!(reachable_at(&graph, 3, true, &target), vec![false, true]);
+        assert_eq!(reachable_at(&graph, 0, true, &target), target);
+    }
+
+    #[test]
+    fn test_self_loop_fast_path() {
+        // `winning_at_each_step` uses `cpre`, which iterates over all successors
+        for graph in [
+            create_self_loop(),
+            create_two_state_graph(),
+            create_dead_end_graph(),
+        ] {
+            for player in [false, true] {
+                for k in 0..8 {
+                    let target: Vec<bool> =
+                        graph.nodes().map(|n| n == graph.node_count - 1).collect();
+                    let expected = winning_at_each_step(&graph, k, player, &target).swap_remove(0);
+                    assert_eq!(reachable_at(&graph, k, player, &target), expected);
+                    assert_eq!(reachable_all(&graph, k, player, &target)[k], expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reachable_at_instrumented() {
+        // time-varying: no early termination
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+        let (wins_at, stats) = reachable_at_instrumented(&graph, 6, false, &target);
+        assert_eq!(wins_at, reachable_at(&graph, 6, false, &target));
+        assert_eq!(
+            stats,
+            SolveStats {
+                iterations: 6,
+                flips: 1,
+                early_fixpoint: false,
+            }
+        );
+
+        // time-invariant: stable after one step, detected by the second
+        let graph = create_self_loop();
+        let (wins_at, stats) = reachable_at_instrumented(&graph, 10, false, &[true]);
+        assert_eq!(wins_at, vec![true]);
+        assert_eq!(
+            stats,
+            SolveStats {
+                iterations: 1,
+                flips: 0,
+                early_fixpoint: true,
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "target set has 3 entries, but the graph has 2 nodes")]
+    fn test_reachable_at_target_size() {
+        reachable_at(&create_two_state_graph(), 6, false, &[false, true, false]);
+    }
+
+    #[test]
+    fn test_strategy_preference() {
+        use crate::temporal_graphs::TemporalGraphBuilder;
+
+        // s0 can move to either target; its edge to s2 was added first
+        let graph = TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", true, None)
+            .node("s2", true, None)
+            .edge("s0", "s2", Formula::True)
+            .edge("s0", "s1", Formula::True)
+            .edge("s1", "s1", Formula::True)
+            .edge("s2", "s2", Formula::True)
+            .build()
+            .unwrap();
+        let target = vec![false, true, true];
+
+        for (preference, choice) in [
+            (StrategyPreference::LowestIndex, 1),
+            (StrategyPreference::HighestIndex, 2),
+            (StrategyPreference::FirstAvailable, 2),
+        ] {
+            let (wins, strategy) = reachable_at_with_strategy(&graph, 1, true, &target, preference);
+            assert_eq!(wins, vec![true, true, true]);
+            // the targets can only stay put
+            assert_eq!(
+                strategy,
+                vec![vec![Some(choice), Some(1), Some(2)]],
+                "{:?}",
+                preference
+            );
+        }
+        assert_eq!(
+            StrategyPreference::default(),
+            StrategyPreference::LowestIndex
+        );
+
+        // owned by the opponent, s0 gets no strategy entry
+        let (wins, strategy) =
+            reachable_at_with_strategy(&graph, 1, false, &target, StrategyPreference::default());
+        assert_eq!(wins, vec![true, true, true]);
+        assert_eq!(strategy, vec![vec![None; 3]]);
+    }
+
+    #[test]
+    fn test_terminal_deadlock_policy() {
+        use crate::formulae::Expr;
+        use crate::temporal_graphs::TemporalGraphBuilder;
+
+        // opponent nodes: `waiting` can move to the target from time 5, `terminal` never
+        let graph = TemporalGraphBuilder::new()
+            .node("waiting", false, None)
+            .node("terminal", false, None)
+            .node("goal", true, None)
+            .edge("goal", "goal", Formula::True)
+            .edge(
+                "waiting",
+                "goal",
+                Formula::Ge(
+                    Box::new(Expr::Var("t".to_string())),
+                    Box::new(Expr::Const(5)),
+                ),
+            )
+            .build()
+            .unwrap();
+        let target = vec![false, false, true];
+        let solve = |k, deadlock| reachable_at_with_policy(&graph, k, true, &target, deadlock);
+
+        assert_eq!(
+            solve(3, DeadlockPolicy::OpponentWins),
+            vec![false, false, true]
+        );
+        assert_eq!(
+            solve(3, DeadlockPolicy::OpponentLoses),
+            vec![true, true, true]
+        );
+        assert_eq!(
+            solve(3, DeadlockPolicy::TerminalOpponentLoses),
+            vec![false, true, true]
+        );
+        // at k = 0 nobody moves, only the target wins
+        assert_eq!(solve(0, DeadlockPolicy::TerminalOpponentLoses), target);
+    }
+
+    #[test]
+    fn test_reachable_all_with_progress() {
+        let graph = create_two_state_graph();
+        let target = vec![false, true];
+        let mut steps = Vec::new();
+        let all = reachable_all_with_progress(&graph, 7, false, &target, |k| steps.push(k));
+        assert_eq!(steps, (0..=7).collect::<Vec<_>>());
+        assert_eq!(all, reachable_all(&graph, 7, false, &target));
+    }
+
+    #[test]
+    fn test_trivial_games() {
+        let empty = TemporalGraph::new(0, HashMap::new(), HashMap::new(), vec![]);
+        for k in [0, 5] {
+            assert_eq!(reachable_at(&empty, k, true, &[]), Vec::<bool>::new());
+            assert_eq!(
+                reachable_at_instrumented(&empty, k, true, &[]).1,
+                SolveStats::default()
+            );
+        }
+        assert_eq!(winning_at_each_step(&empty, 3, true, &[]), vec![vec![]; 4]);
+
+        // at k = 0 the target is the winning set, even if it cannot be kept
+        let graph = create_dead_end_graph();
+        for target in [vec![true, false], vec![false, true]] {
+            assert_eq!(reachable_at(&graph, 0, true, &target), target);
+            assert_eq!(reachable_at(&graph, 0, false, &target), target);
+        }
     }
 }
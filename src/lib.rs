@@ -1,4 +1,8 @@
+pub mod bitset;
 pub mod formulae;
 pub mod game;
 pub mod parser;
+pub mod solve;
 pub mod temporal_graphs;
+
+pub use solve::{SolveError, SolveOptions, SolveResult, solve};
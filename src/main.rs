@@ -1,27 +1,67 @@
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, IsTerminal, Read};
+use std::ops::RangeInclusive;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::Path;
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use clap::Parser;
-use ontime::game::reachable_at;
-use ontime::parser::tg_parser::{NIDListParser, TemporalGraphParser};
+use flate2::read::GzDecoder;
+use ontime::SolveError;
+use ontime::game::{
+    reachable_all_with_progress, reachable_at, reachable_at_cancellable,
+    reachable_at_capped, reachable_at_instrumented, reachable_in_window, winning_count,
+};
+use ontime::parser::Directives;
+use ontime::solve::{
+    DEFAULT_TIME_BOUND, determine_player, determine_target_set, determine_time_bound,
+    parse_graph_with_directives, parse_index_predicate, parse_player, parse_target_ids, read_meta,
+};
+use ontime::temporal_graphs::TemporalGraph;
 
 /// A solver for punctual reachability games on temporal graphs
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
+#[command(group(clap::ArgGroup::new("explicit_time_bound").args(["time_to_reach", "k"])))]
 struct Args {
     /// Path to the temporal graph input file (use '-' for stdin)
     input_file: Option<String>,
     
-    /// Target set of nodes (comma-separated node IDs)
+    /// Target set of nodes (comma-separated node IDs, ranges like `v0..v3` allowed,
+    /// or `all` for every node unless a node has that id)
     #[arg(long, default_value = "v0")]
     target_set: String,
     
-    /// Time to reach the target set (will be overridden by .meta file if present)
-    #[arg(long, default_value = "10")]
-    time_to_reach: usize,
+    /// Time to reach the target set [default: 10] (a time bound given in the input file
+    /// or its .meta file takes priority, with a warning)
+    #[arg(long)]
+    time_to_reach: Option<usize>,
+
+    /// Like --time-to-reach (so a time bound in an input file takes priority, with a warning),
+    /// but when reading from stdin it always takes priority over a `// time_bound:` comment
+    /// in the input, without a warning
+    #[arg(long = "k", conflicts_with = "time_to_reach")]
+    k: Option<usize>,
+
+    /// Let --time-to-reach (or --k) take priority over a time bound in the input file
+    /// or its .meta file
+    #[arg(long, requires = "explicit_time_bound")]
+    force_time_to_reach: bool,
     
+    /// Select as targets all nodes whose label starts with this prefix
+    /// (instead of --target-set; nodes without a label are never selected)
+    #[arg(long)]
+    target_label_prefix: Option<String>,
+
+    /// Select as targets all nodes whose index (in order of declaration, from 0)
+    /// satisfies this formula in one variable, e.g. `(= (mod n 2) 0)`
+    /// (instead of --target-set)
+    #[arg(long, conflicts_with = "target_label_prefix")]
+    target_expr: Option<String>,
+
     /// Output only timing information (compatible with GGG benchmark)
     #[arg(long)]
     time_only: bool,
@@ -33,50 +73,484 @@ struct Args {
     /// Output in CSV format
     #[arg(long)]
     csv: bool,
+
+    /// Output in JSON format
+    #[arg(long)]
+    json: bool,
+
+    /// Solve for every time bound in a range, e.g. `0..=20` (overrides the time bound)
+    #[arg(long, value_parser = parse_time_range)]
+    time_range: Option<RangeInclusive<usize>>,
+
+    /// Reach the target at some time in a window `a:b` instead of at exactly
+    /// the time bound (overrides the time bound, which becomes `b`)
+    #[arg(long, value_parser = parse_window, conflicts_with_all = ["time_range", "timeout"])]
+    window: Option<RangeInclusive<usize>>,
+
+    /// If no edge's availability changes after some time before the time bound,
+    /// skip the repetitive steps after it (see `TemporalGraph::max_meaningful_time`)
+    #[arg(long, conflicts_with_all = ["window", "timeout"])]
+    auto_horizon: bool,
+
+    /// Remove all self-loops before solving, so that every step moves to another node
+    /// (a stuck node loses for the reaching player, and wins for the opponent as usual)
+    #[arg(long)]
+    no_self_loops: bool,
+
+    /// Solve every .tg file in a directory, printing one CSV row per file
+    #[arg(long)]
+    batch: Option<String>,
+
+    /// With --batch, the number of files solved concurrently
+    /// (rows are still printed in the order of the file names)
+    #[arg(long, default_value_t = 1, requires = "batch",
+          value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    jobs: usize,
+
+    /// Output only the number of winning nodes and their ratio to all nodes
+    /// (with --csv, appended as two columns; with --json, added as `count` and `ratio`)
+    #[arg(long)]
+    count_only: bool,
+
+    /// Print statistics about the temporal graph instead of solving
+    #[arg(long)]
+    stats: bool,
+
+    /// Decompress gzipped input read from stdin (files are detected automatically)
+    #[arg(long)]
+    gzip: bool,
+
+    /// With --csv, append the winning set (sorted node ids, `;`-separated) as a certificate column
+    #[arg(long)]
+    certificate: bool,
+
+    /// Report dead nodes (no available outgoing edge up to the time bound,
+    /// or no incoming edge) instead of solving
+    #[arg(long)]
+    lint: bool,
+
+    /// Only check that the input parses and is valid, with every edge formula
+    /// usable as an availability constraint, instead of solving
+    #[arg(long)]
+    check: bool,
+
+    /// Print the times up to the time bound at which an edge between the two
+    /// given nodes is available, instead of solving
+    #[arg(long, num_args = 2, value_names = ["FROM", "TO"])]
+    trace_edge: Option<Vec<String>>,
+
+    /// Print the temporal graph as GraphML instead of solving
+    #[arg(long)]
+    graphml: bool,
+
+    /// List the edges whose availability is constrained (not always available)
+    /// instead of solving
+    #[arg(long)]
+    show_constraints: bool,
+
+    /// Solve once for each line of this file, each a comma-separated target set,
+    /// printing one CSV row per target set
+    #[arg(long)]
+    targets_file: Option<String>,
+
+    /// Give up solving after this many seconds, reporting `timeout` (exit code 124)
+    #[arg(long, value_parser = parse_timeout, conflicts_with = "time_range")]
+    timeout: Option<f64>,
+
+    /// Print a human-readable report, naming nodes by their labels
+    #[arg(long)]
+    pretty: bool,
+
+    /// The reaching player, 0 or 1 as in `owner[0]`/`owner[1]` [default: 0]
+    /// (takes priority over a player given in the input file or its .meta file)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=1))]
+    player: Option<u8>,
+
+    /// Only report whether this node is winning: prints WIN (exit code 0) or LOSE (exit code 1)
+    #[arg(long)]
+    start: Option<String>,
 }
 
-fn read_time_bound_from_meta(file_path: &str) -> Option<usize> {
-    // Convert .tg file to .meta file path
-    let meta_path = file_path.replace(".tg", ".meta");
-    
-    if let Ok(mut file) = File::open(&meta_path) {
-        let mut content = String::new();
-        if file.read_to_string(&mut content).is_ok() {
-            for line in content.lines() {
-                if let Some(time_bound_str) = line.strip_prefix("time_bound: ") {
-                    if let Ok(time_bound) = time_bound_str.trim().parse::<usize>() {
-                        return Some(time_bound);
-                    }
-                }
+/// Errors reported by the command line interface
+enum CliError {
+    /// Reading the input failed
+    Io(io::Error),
+    /// The temporal graph could not be parsed
+    GraphParse(String),
+    /// The target set could not be parsed
+    TargetParse(String),
+    /// The input parsed, but is inconsistent (e.g. an edge to an undeclared node)
+    Validation(String),
+    /// The command line selects nodes or edges the graph does not have (exit code 2)
+    Selection(String),
+}
+
+impl CliError {
+    /// The exit code of the process when it fails with this error
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Selection(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Io(e) => write!(f, "I/O error: {}", e),
+            CliError::GraphParse(e) => write!(f, "failed to parse temporal graph: {}", e),
+            CliError::TargetParse(e) => write!(f, "failed to parse target set: {}", e),
+            CliError::Validation(e) => write!(f, "invalid temporal graph: {}", e),
+            CliError::Selection(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+// `main` prints errors via Debug: show the message, not the enum structure
+impl std::fmt::Debug for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for CliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CliError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        CliError::Io(e)
+    }
+}
+
+impl From<SolveError> for CliError {
+    fn from(e: SolveError) -> Self {
+        match e {
+            SolveError::GraphParse(e) => CliError::GraphParse(e),
+            SolveError::TargetParse(e) => CliError::TargetParse(e),
+            SolveError::Validation(e) => CliError::Validation(e),
+        }
+    }
+}
+
+fn parse_timeout(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(seconds),
+        _ => Err(format!("invalid timeout '{}': expected a non-negative number of seconds", s)),
+    }
+}
+
+fn parse_time_range(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|e| format!("invalid time bound '{}': {}", n, e))
+    };
+    // normalised to an inclusive range, or `None` if that would be empty
+    let (lo, hi) = if let Some((lo, hi)) = s.split_once("..=") {
+        (parse(lo)?, Some(parse(hi)?))
+    } else if let Some((lo, hi)) = s.split_once("..") {
+        (parse(lo)?, parse(hi)?.checked_sub(1))
+    } else {
+        return Err(format!("expected a range like '0..=20', got '{}'", s));
+    };
+    match hi {
+        Some(hi) if lo <= hi => Ok(lo..=hi),
+        _ => Err(format!("empty time range '{}'", s)),
+    }
+}
+
+fn parse_window(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let Some((a, b)) = s.split_once(':') else {
+        return Err(format!("expected a window like '2:5', got '{}'", s));
+    };
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|e| format!("invalid time '{}': {}", n, e))
+    };
+    let (a, b) = (parse(a)?, parse(b)?);
+    if a > b {
+        return Err(format!("empty window '{}'", s));
+    }
+    Ok(a..=b)
+}
+
+// The time bound of the input, warning if it overrides an explicit --time-to-reach or --k
+// (unless --force-time-to-reach is given, in which case that one is used).
+// On stdin, --k is used whatever the input says.
+fn time_bound(args: &Args, directives: &Directives, file_path: Option<&str>) -> usize {
+    let stdin = file_path.is_none_or(|p| p == "-");
+    let forced = args.force_time_to_reach || (stdin && args.k.is_some());
+    let flag = match args.k {
+        Some(_) => "--k",
+        None => "--time-to-reach",
+    };
+    match args.k.or(args.time_to_reach) {
+        Some(cli) if forced => cli,
+        Some(cli) => {
+            let k = determine_time_bound(directives, file_path, cli);
+            if k != cli {
+                eprintln!(
+                    "Warning: {} {} is overridden by the time bound {} of the input \
+                     (use --force-time-to-reach to keep it)",
+                    flag, cli, k
+                );
             }
+            k
         }
+        None => determine_time_bound(directives, file_path, DEFAULT_TIME_BOUND),
     }
-    None
 }
 
-fn extract_time_bound_from_tg_content(content: &str) -> Option<usize> {
-    // Look for time_bound in comment lines
-    for line in content.lines() {
-        if let Some(time_bound_str) = line.strip_prefix("// time_bound: ") {
-            if let Ok(time_bound) = time_bound_str.trim().parse::<usize>() {
-                return Some(time_bound);
+// The reaching player: --player, or else as given by the input (player `true` by default)
+fn reaching_player(args: &Args, directives: &Directives, file_path: Option<&str>) -> bool {
+    match args.player {
+        Some(player) => player == 0,
+        None => {
+            for (_, value) in directives.iter().filter(|(key, _)| key == "player") {
+                if let Err(e) = parse_player(value) {
+                    eprintln!("Warning: ignoring player directive: {}", e);
+                }
             }
+            determine_player(directives, file_path, true)
         }
     }
-    None
 }
 
-fn extract_targets_from_tg_content(content: &str) -> Option<String> {
-    // Look for targets in comment lines
-    for line in content.lines() {
-        if let Some(targets_str) = line.strip_prefix("// targets: ") {
-            return Some(targets_str.trim().to_string());
+// Solve a single file for batch mode
+fn solve_file(file_path: &str, args: &Args) -> Result<(), CliError> {
+    let input = std::fs::read_to_string(file_path)?;
+    let (graph, directives) = parse_graph_with_directives(&input)?;
+    let graph = match args.no_self_loops {
+        true => graph.without_self_loops(),
+        false => graph,
+    };
+    let k = time_bound(args, &directives, Some(file_path));
+    let target_ids =
+        parse_target_ids(&determine_target_set(&directives, Some(file_path), &args.target_set))?;
+    let target_at_k = graph.nodes_selected_from_ids(&target_ids);
+    let player = reaching_player(args, &directives, Some(file_path));
+    reachable_at(&graph, k, player, &target_at_k);
+    Ok(())
+}
+
+// Solve a single file for batch mode, timing it; a panic is reported as an error
+fn solve_file_isolated(file_path: &str, args: &Args) -> (&'static str, f64) {
+    let start_time = Instant::now();
+    let status = match panic::catch_unwind(AssertUnwindSafe(|| solve_file(file_path, args))) {
+        Ok(Ok(())) => "solved",
+        Ok(Err(_)) | Err(_) => "error",
+    };
+    (status, start_time.elapsed().as_secs_f64())
+}
+
+// Solve every .tg file in a directory, one GGG CSV row per file (sorted by name).
+// The files are solved by `args.jobs` worker threads, each taking the next unsolved file.
+fn solve_batch(dir: &str, args: &Args) -> Result<(), CliError> {
+    let mut files: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tg"))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; files.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..args.jobs.min(files.len()) {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(file_path) = files.get(i) else {
+                        break;
+                    };
+                    let result = solve_file_isolated(file_path, args);
+                    results.lock().unwrap()[i] = Some(result);
+                }
+            });
         }
+    });
+
+    let results = results.into_inner().unwrap();
+    for (file_path, result) in files.iter().zip(results) {
+        let (status, time) = result.expect("every file is solved");
+        println!(
+            "Ontime Punctual Reachability Solver,{},{},{:.6}",
+            file_path, status, time
+        );
     }
-    None
+    Ok(())
 }
 
-fn main() -> io::Result<()> {
+// number of winning nodes, and their ratio to all nodes of the graph
+fn count_and_ratio(graph: &TemporalGraph, wins: &[bool]) -> (usize, f64) {
+    let count = winning_count(wins);
+    let ratio = match graph.node_count {
+        0 => 0.0,
+        n => count as f64 / n as f64,
+    };
+    (count, ratio)
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Read the input file (or stdin), decompressing gzipped input.
+// Files are decompressed if they end in .gz or start with the gzip magic bytes,
+// stdin only with --gzip.
+fn read_input(args: &Args) -> Result<String, CliError> {
+    let mut bytes = Vec::new();
+    let gzipped = match args.input_file.as_deref() {
+        Some(file_path) if file_path != "-" => {
+            File::open(Path::new(file_path))?.read_to_end(&mut bytes)?;
+            file_path.ends_with(".gz") || bytes.starts_with(&GZIP_MAGIC)
+        }
+        // Default to stdin if no file specified
+        _ => {
+            io::stdin().read_to_end(&mut bytes)?;
+            args.gzip
+        }
+    };
+
+    let mut input = String::new();
+    if gzipped {
+        GzDecoder::new(bytes.as_slice()).read_to_string(&mut input)?;
+    } else {
+        input = String::from_utf8(bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    }
+    Ok(input)
+}
+
+// Select the target nodes, warning about (and ignoring) unknown ids.
+// The single id `all` selects every node, unless the graph has a node of that name.
+fn select_target(graph: &TemporalGraph, target_ids: &HashSet<String>) -> Vec<bool> {
+    if target_ids.len() == 1
+        && target_ids.contains("all")
+        && !graph.node_id_map.contains_key("all")
+    {
+        return graph.all_nodes_selected();
+    }
+    match graph.nodes_selected_from_ids_checked(target_ids) {
+        Ok(target_at_k) => target_at_k,
+        Err(unknown) => {
+            eprintln!(
+                "Warning: ignoring unknown target nodes: {}",
+                unknown.join(", ")
+            );
+            graph.nodes_selected_from_ids(target_ids)
+        }
+    }
+}
+
+// Solve once per target set in the file, one CSV row each: file, targets, winning nodes.
+// Lines that fail to parse are skipped with a warning.
+fn solve_targets_file(
+    graph: &TemporalGraph,
+    k: usize,
+    player: bool,
+    targets_file: &str,
+    args: &Args,
+) -> Result<(), CliError> {
+    let filename = args.input_file.as_deref().unwrap_or("stdin");
+    let content = std::fs::read_to_string(targets_file)?;
+    for (n, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let target_ids = match parse_target_ids(line) {
+            Ok(target_ids) => target_ids,
+            Err(e) => {
+                eprintln!(
+                    "Warning: skipping line {} of {}: {}",
+                    n + 1,
+                    targets_file,
+                    CliError::from(e)
+                );
+                continue;
+            }
+        };
+        let target_at_k = select_target(graph, &target_ids);
+        let wins_at = reachable_at(graph, k, player, &target_at_k);
+        println!(
+            "Ontime Punctual Reachability Solver,{},{},{}",
+            filename,
+            graph.sorted_ids_from_nodes_vec(&target_at_k).join(";"),
+            graph.sorted_ids_from_nodes_vec(&wins_at).join(";")
+        );
+    }
+    Ok(())
+}
+
+// Node ids by index
+fn node_ids(graph: &TemporalGraph) -> Vec<&str> {
+    let mut ids = vec![""; graph.node_count];
+    for (id, &n) in &graph.node_id_map {
+        ids[n] = id.as_str();
+    }
+    ids
+}
+
+// Node names for the --pretty report: labels where present, ids otherwise, in id order
+fn pretty_names(graph: &TemporalGraph, nodes: &[bool]) -> Vec<String> {
+    graph
+        .sorted_ids_from_nodes_vec(nodes)
+        .into_iter()
+        .map(|id| match graph.label(graph.node_id_map[&id]) {
+            Some(label) => label.to_string(),
+            None => id,
+        })
+        .collect()
+}
+
+// Print the --pretty report of a solve
+fn print_pretty(
+    graph: &TemporalGraph,
+    k: usize,
+    target_at_k: &[bool],
+    wins_at: &[bool],
+    solve_time: Duration,
+) {
+    println!("Nodes: {}", graph.node_count);
+    println!("Time bound: {}", k);
+    println!("Target nodes:");
+    for name in pretty_names(graph, target_at_k) {
+        println!("  {}", name);
+    }
+    println!("Winning nodes:");
+    for name in pretty_names(graph, wins_at) {
+        println!("  {}", name);
+    }
+    println!("Solve time: {:.6} s", solve_time.as_secs_f64());
+}
+
+// Report a cancelled solve in the requested format and exit with code 124
+fn report_timeout(args: &Args, k: usize, start_time: Instant) -> ! {
+    let filename = args.input_file.as_deref().unwrap_or("stdin");
+    let time = start_time.elapsed().as_secs_f64();
+    if args.csv {
+        println!("Ontime Punctual Reachability Solver,{},timeout,{:.6}", filename, time);
+    } else if args.json {
+        let output = serde_json::json!({
+            "file": filename,
+            "k": k,
+            "status": "timeout",
+            "time": time,
+        });
+        println!("{}", output);
+    } else {
+        eprintln!("Timeout after {:.6} seconds", time);
+    }
+    std::process::exit(124);
+}
+
+fn main() -> Result<(), CliError> {
     let args = Args::parse();
     
     // Handle solver name request
@@ -85,84 +559,310 @@ fn main() -> io::Result<()> {
         return Ok(());
     }
 
+    if let Some(dir) = &args.batch {
+        return solve_batch(dir, &args);
+    }
+
     let start_time = Instant::now();
-    
+    let result = run(&args, start_time);
+    if result.is_err() && args.csv {
+        // CSV format compatible with GGG: report the failure as a row
+        let filename = args.input_file.as_deref().unwrap_or("stdin");
+        println!("Ontime Punctual Reachability Solver,{},error,{:.6}",
+                 filename, start_time.elapsed().as_secs_f64());
+    }
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(e.exit_code());
+    }
+    Ok(())
+}
+
+fn run(args: &Args, start_time: Instant) -> Result<(), CliError> {
     // Read input (from file or stdin)
-    let input = if let Some(file_path) = &args.input_file {
-        if file_path == "-" {
-            // Read from stdin
-            let mut input = String::new();
-            io::stdin().read_to_string(&mut input)?;
-            input
-        } else {
-            // Read from file
-            let path = Path::new(file_path);
-            let mut file = File::open(path)?;
-            let mut input = String::new();
-            file.read_to_string(&mut input)?;
-            input
+    let input = read_input(args)?;
+
+    // Parse the file
+    let (graph, directives) = parse_graph_with_directives(&input)?;
+    let graph = match args.no_self_loops {
+        true => graph.without_self_loops(),
+        false => graph,
+    };
+
+    if args.check {
+        graph.validate().map_err(CliError::Validation)?;
+        // the parser already compiled every formula, but check them independently
+        let ids = node_ids(&graph);
+        for edge in graph.edges() {
+            if let Err(e) = edge.formula().clone().as_closure() {
+                return Err(CliError::Validation(format!(
+                    "edge {} -> {}: {}",
+                    ids[*edge.source()],
+                    ids[*edge.target()],
+                    e
+                )));
+            }
+        }
+        println!("OK");
+        return Ok(());
+    }
+
+    if args.stats {
+        println!("{}", graph.stats());
+        return Ok(());
+    }
+
+    if args.graphml {
+        print!("{}", graph.to_graphml());
+        return Ok(());
+    }
+
+    if args.show_constraints {
+        let ids = node_ids(&graph);
+        let mut constrained = graph.constrained_edges().peekable();
+        if constrained.peek().is_none() {
+            println!("No constrained edges");
+        }
+        for (from, to, formula) in constrained {
+            println!("{} -> {}: {}", ids[from], ids[to], formula);
+        }
+        return Ok(());
+    }
+
+    // a .meta file sits next to the uncompressed name
+    let meta_path = args.input_file.as_deref().map(|p| p.strip_suffix(".gz").unwrap_or(p));
+    let k = time_bound(args, &directives, meta_path);
+
+    // the .meta file may also name the reaching player and a start node
+    let meta = meta_path.filter(|p| *p != "-").and_then(read_meta).unwrap_or_default();
+    for key in &meta.unknown_keys {
+        eprintln!("Warning: ignoring unknown key in .meta file: {}", key);
+    }
+    for e in &meta.invalid_values {
+        eprintln!("Warning: ignoring value in .meta file: {}", e);
+    }
+    let player = reaching_player(args, &directives, meta_path);
+
+    if args.lint {
+        let mut dead = vec![false; graph.node_count];
+        for node in graph.dead_nodes(k) {
+            dead[node] = true;
+        }
+        let dead = graph.sorted_ids_from_nodes_vec(&dead);
+        if dead.is_empty() {
+            println!("No dead nodes");
+        }
+        for id in dead {
+            println!("Dead node: {}", id);
+        }
+        return Ok(());
+    }
+
+    if let Some(ends) = &args.trace_edge {
+        let (from_id, to_id) = (&ends[0], &ends[1]);
+        let node = |id: &String| {
+            graph
+                .node_id_map
+                .get(id)
+                .copied()
+                .ok_or_else(|| CliError::Selection(format!("Unknown node: {}", id)))
+        };
+        let (from, to) = (node(from_id)?, node(to_id)?);
+        if !graph.successors(from).any(|s| s == to) {
+            return Err(CliError::Selection(format!("No edge {} -> {}", from_id, to_id)));
+        }
+        println!(
+            "{} -> {}: {:?}",
+            from_id,
+            to_id,
+            graph.availability_trace(from, to, k)
+        );
+        return Ok(());
+    }
+
+    // solve for several target sets, reusing the parsed graph
+    if let Some(targets_file) = &args.targets_file {
+        return solve_targets_file(&graph, k, player, targets_file, args);
+    }
+
+    // w is the winning set at time k
+    let (target_at_k, target_set) = match (&args.target_label_prefix, &args.target_expr) {
+        (Some(prefix), _) => (
+            graph.nodes_matching_label(|label| label.starts_with(prefix.as_str())),
+            format!("label prefix {}", prefix),
+        ),
+        (None, Some(expr)) => (
+            graph.nodes_matching_index(parse_index_predicate(expr)?),
+            format!("index predicate {}", expr),
+        ),
+        (None, None) => {
+            let target_set = determine_target_set(&directives, meta_path, &args.target_set);
+            let target_ids = parse_target_ids(&target_set)?;
+            (select_target(&graph, &target_ids), target_set)
         }
-    } else {
-        // Default to stdin if no file specified
-        let mut input = String::new();
-        io::stdin().read_to_string(&mut input)?;
-        input
     };
+    if !target_at_k.contains(&true) {
+        return Err(CliError::Selection(format!(
+            "Empty target set: no node matches '{}'",
+            target_set
+        )));
+    }
 
-    // Parse the file
-    let parser = TemporalGraphParser::new();
-    let graph = parser.parse(&input).expect("Parse error");
-
-    // Determine time bound - priority order:
-    // 1. From TG file content (works with stdin)
-    // 2. From .meta file (only when file path available)
-    // 3. Command line argument (fallback)
-    let k: usize = extract_time_bound_from_tg_content(&input)
-        .or_else(|| {
-            if let Some(file_path) = &args.input_file {
-                if file_path != "-" {
-                    read_time_bound_from_meta(file_path)
-                } else {
-                    None
+    // resolve the start node before solving
+    let start = match args.start.as_ref().or(meta.start.as_ref()) {
+        Some(id) => match graph.node_id_map.get(id) {
+            Some(&node) => Some(node),
+            None => return Err(CliError::Selection(format!("Unknown start node: {}", id))),
+        },
+        None => None,
+    };
+    
+    // sweep over a range of time bounds, sharing work between them
+    if let Some(range) = &args.time_range {
+        let filename = args.input_file.as_deref().unwrap_or("stdin");
+        let max_k = *range.end();
+        // progress on an interactive terminal, at most every 100ms
+        let interactive = io::stderr().is_terminal();
+        let mut last_report = Instant::now();
+        let all = reachable_all_with_progress(&graph, max_k, player, &target_at_k, |k| {
+            let due = k == max_k || last_report.elapsed() >= Duration::from_millis(100);
+            if interactive && due {
+                eprint!("\rSolved horizon {}/{}", k, max_k);
+                if k == max_k {
+                    eprintln!();
                 }
+                last_report = Instant::now();
+            }
+        });
+        for k in range.clone() {
+            let wins = graph.sorted_ids_from_nodes_vec(&all[k]);
+            if args.csv {
+                println!(
+                    "Ontime Punctual Reachability Solver,{},{},{}",
+                    filename,
+                    k,
+                    wins.join(";")
+                );
             } else {
-                None
+                println!("W_0[k={}] = {:?}", k, wins);
             }
-        })
-        .unwrap_or(args.time_to_reach);
+        }
+        return Ok(());
+    }
 
-    // Determine target set - priority order:
-    // 1. From TG file content (works with stdin)
-    // 2. Command line argument (fallback)
-    let target_set = extract_targets_from_tg_content(&input)
-        .unwrap_or(args.target_set.clone());
+    // a window ends at the time bound reported below
+    let k = args.window.as_ref().map_or(k, |window| *window.end());
 
-    // parse target
-    let parser = NIDListParser::new();
-    let v = parser.parse(&target_set).expect("Failed to read target");
-    let target_ids: std::collections::HashSet<_> = v.iter().cloned().collect();
+    // with --auto-horizon, the time after which all steps are the same, if before k
+    let horizon = match args.auto_horizon {
+        true => graph.max_meaningful_time().filter(|&horizon| horizon < k),
+        false => None,
+    };
 
-    // w is the winning set at time k
-    let target_at_k: Vec<bool> = graph.nodes_selected_from_ids(&target_ids);
-    
-    // compute the reachable set at time 0
-    let wins_at = reachable_at(&graph, k, true, &target_at_k);
+    // compute the reachable set at time 0, with statistics unless cancellable or capped
+    let (wins_at, solve_stats) = match (&args.window, args.timeout) {
+        (Some(window), _) => {
+            let (a, b) = (*window.start(), *window.end());
+            (reachable_in_window(&graph, a, b, player, &target_at_k), None)
+        }
+        (None, Some(seconds)) => {
+            // a watchdog thread cancels the solver once the time is up
+            let cancel = Arc::new(AtomicBool::new(false));
+            let watchdog = Arc::clone(&cancel);
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_secs_f64(seconds));
+                watchdog.store(true, Ordering::Relaxed);
+            });
+            match reachable_at_cancellable(&graph, k, player, &target_at_k, &cancel) {
+                Some(wins_at) => (wins_at, None),
+                None => report_timeout(args, k, start_time),
+            }
+        }
+        (None, None) => match horizon {
+            Some(horizon) => (
+                reachable_at_capped(&graph, k, player, &target_at_k, horizon),
+                None,
+            ),
+            None => {
+                let (wins_at, stats) = reachable_at_instrumented(&graph, k, player, &target_at_k);
+                (wins_at, Some(stats))
+            }
+        },
+    };
     
     let solve_time = start_time.elapsed();
     
     // Output based on requested format
-    if args.time_only {
+    if let Some(node) = start {
+        // yes/no answer for a single start node, reported via the exit code
+        if args.pretty {
+            let mut single = vec![false; graph.node_count];
+            single[node] = true;
+            println!(
+                "Node {} is {} at horizon {}",
+                pretty_names(&graph, &single)[0],
+                if wins_at[node] { "winning" } else { "losing" },
+                k
+            );
+            std::process::exit(if wins_at[node] { 0 } else { 1 });
+        }
+        if wins_at[node] {
+            println!("WIN");
+            std::process::exit(0);
+        } else {
+            println!("LOSE");
+            std::process::exit(1);
+        }
+    } else if args.pretty {
+        print_pretty(&graph, k, &target_at_k, &wins_at, solve_time);
+    } else if args.time_only {
         // Output only timing (for GGG benchmark compatibility)
         println!("{:.6}", solve_time.as_secs_f64());
     } else if args.csv {
         // CSV format compatible with GGG
         let filename = args.input_file.as_deref().unwrap_or("stdin");
-        println!("Ontime Punctual Reachability Solver,{},solved,{:.6}",
-                 filename, solve_time.as_secs_f64());
+        let mut row = format!("Ontime Punctual Reachability Solver,{},solved,{:.6}",
+                              filename, solve_time.as_secs_f64());
+        if args.count_only {
+            let (count, ratio) = count_and_ratio(&graph, &wins_at);
+            row.push_str(&format!(",{},{:.6}", count, ratio));
+        }
+        if args.certificate {
+            row.push(',');
+            row.push_str(&graph.sorted_ids_from_nodes_vec(&wins_at).join(";"));
+        }
+        println!("{}", row);
+    } else if args.json {
+        let filename = args.input_file.as_deref().unwrap_or("stdin");
+        let mut output = serde_json::json!({
+            "file": filename,
+            "k": k,
+            "target": graph.sorted_ids_from_nodes_vec(&target_at_k),
+            "winning": graph.sorted_ids_from_nodes_vec(&wins_at),
+            "time": solve_time.as_secs_f64(),
+        });
+        if let Some(stats) = solve_stats {
+            output["stats"] = serde_json::json!({
+                "iterations": stats.iterations,
+                "flips": stats.flips,
+                "early_fixpoint": stats.early_fixpoint,
+            });
+        }
+        if args.count_only {
+            let (count, ratio) = count_and_ratio(&graph, &wins_at);
+            output["count"] = serde_json::json!(count);
+            output["ratio"] = serde_json::json!(ratio);
+        }
+        println!("{}", output);
+    } else if args.count_only {
+        let (count, ratio) = count_and_ratio(&graph, &wins_at);
+        println!("{} {:.6}", count, ratio);
     } else {
-        // Standard output
-        println!("W_{} = {:?}", k, graph.ids_from_nodes_vec(&target_at_k));
-        println!("W_0 = {:?}", graph.ids_from_nodes_vec(&wins_at));
+        // Standard output; for k = 0 the target is the winning set, print it once
+        if k > 0 {
+            println!("W_{} = {:?}", k, graph.sorted_ids_from_nodes_vec(&target_at_k));
+        }
+        println!("W_0 = {:?}", graph.sorted_ids_from_nodes_vec(&wins_at));
     }
 
     Ok(())
@@ -1,11 +1,12 @@
-use std::collections::HashMap;
+use std::io::BufRead;
 
-use lalrpop_util::lalrpop_mod;
+use lalrpop_util::lexer::Token;
+use lalrpop_util::{ParseError, lalrpop_mod};
 
-use crate::formulae::Formula;
-use crate::temporal_graphs::{Edge, Node, TemporalGraph};
+use crate::formulae::{Expr, Formula};
+use crate::temporal_graphs::{TemporalGraph, TemporalGraphBuilder};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NodeAttr {
     Label(String),
     Owner(bool),
@@ -14,71 +15,382 @@ pub enum NodeAttr {
 #[derive(Debug)]
 pub enum ParsedLine {
     Node(String, Vec<NodeAttr>),
-    Edge(String, String, Option<Formula>),
+    /// An edge with its availability formula (always available if `None`) and weight
+    Edge(String, String, Option<Formula>, i64),
+    /// A named constant `#define NAME VALUE`, substituted into all edge formulas
+    Define(String, i64),
+    /// A `// key: value` comment, with key and trimmed value
+    Directive(String, String),
     Empty,
 }
 
+/// The `// key: value` directives of a `.tg` file, in file order
+pub type Directives = Vec<(String, String)>;
+
 lalrpop_mod!(pub tg_parser, "/parser/tg_parser.rs"); // LALRPOP parser module
 lalrpop_mod!(pub formula, "/parser/formula.rs"); // LALRPOP parser module
 
-pub fn temporal_graph_from_lines(lines: Vec<ParsedLine>) -> TemporalGraph {
-    // first collect all nodes and edges
-    let mut node_lines = Vec::new();
-    let mut edge_lines = Vec::new();
-    for item in lines {
-        match item {
-            ParsedLine::Node(_, _) => node_lines.push(item),
-            ParsedLine::Edge(_, _, _) => edge_lines.push(item),
-            ParsedLine::Empty => {}
+/// Desugars an availability interval `@[lo,hi]` into a formula over [`TIME_VAR_PLACEHOLDER`]:
+/// `(and (>= t lo) (<= t hi))`, or a single bound if the other one is omitted.
+/// An interval without bounds is always available.
+pub fn interval_formula(lo: Option<i64>, hi: Option<i64>) -> Formula {
+    let t = || Box::new(Expr::Var(TIME_VAR_PLACEHOLDER.to_string()));
+    let lower = lo.map(|lo| Formula::Ge(t(), Box::new(Expr::Const(lo))));
+    let upper = hi.map(|hi| Formula::Le(t(), Box::new(Expr::Const(hi))));
+    match (lower, upper) {
+        (Some(lower), Some(upper)) => Formula::And(vec![lower, upper]),
+        (Some(bound), None) | (None, Some(bound)) => bound,
+        (None, None) => Formula::True,
+    }
+}
+
+/// A formula that failed to parse, see [`parse_formula`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormulaParseError {
+    /// Byte offset of the error in the formula, unknown for invalid values
+    /// such as an out of range integer literal
+    pub position: Option<usize>,
+    /// The found token, or `None` at the end of the formula
+    pub found: Option<String>,
+    /// The kinds of token accepted at `position`: `integer`, `variable`,
+    /// `end of formula` or a literal token in backticks like `` `(` ``
+    pub expected: Vec<String>,
+    /// Set instead of the above for errors that are not about the syntax
+    pub message: Option<String>,
+}
+
+impl std::fmt::Display for FormulaParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(message) = &self.message {
+            return write!(f, "{}", message);
+        }
+        match &self.found {
+            Some(found) => write!(f, "unexpected `{}`", found)?,
+            None => write!(f, "unexpected end of formula")?,
+        }
+        if let Some(position) = self.position {
+            write!(f, " at position {}", position)?;
         }
+        match self.expected.as_slice() {
+            [] => Ok(()),
+            [one] => write!(f, ", expected {}", one),
+            [init @ .., last] => write!(f, ", expected one of {} or {}", init.join(", "), last),
+        }
+    }
+}
+
+impl std::error::Error for FormulaParseError {}
+
+// the kind of token an expected token of the formula grammar stands for:
+// regular expressions are named, literal tokens (in quotes) are put in backticks
+fn token_kind(expected: &str) -> String {
+    match expected {
+        r##"r#"[A-Za-z]+"#"## => "variable".to_string(),
+        e if e.starts_with("r#") => "integer".to_string(),
+        e => format!("`{}`", e.trim_matches('"')),
     }
+}
+
+/// Parses a formula like [`formula::FormulaParser`], reporting where the formula
+/// is malformed and which tokens would be accepted there.
+/// Sugar such as `(every 2)` is over the default time variable `t`.
+pub fn parse_formula(input: &str) -> Result<Formula, FormulaParseError> {
+    parse_formula_over(input, DEFAULT_TIME_VAR)
+}
 
-    // Map string node IDs to indices
-    let mut node_id_map = HashMap::new();
-    let mut node_attrs: HashMap<Node, HashMap<String, NodeAttr>> = HashMap::new();
-    let mut next_idx = 0;
-
-    for item in &node_lines {
-        if let ParsedLine::Node(id, attrs) = item {
-            let idx = *node_id_map.entry(id.clone()).or_insert_with(|| {
-                let i = next_idx;
-                next_idx += 1;
-                i
-            });
-
-            let mut attr_map = HashMap::<String, NodeAttr>::new();
-
-            for a in attrs {
-                match a {
-                    NodeAttr::Owner(_) => {
-                        attr_map.insert("owner".to_string(), a.clone());
-                    }
-                    NodeAttr::Label(_) => {
-                        attr_map.insert("label".to_string(), a.clone());
-                    }
-                }
+/// Parses a formula like [`parse_formula`], with sugar such as `(every 2)`
+/// over the time variable `time_var`.
+pub fn parse_formula_over(input: &str, time_var: &str) -> Result<Formula, FormulaParseError> {
+    let syntax_error = |position, found: Option<&str>, expected: Vec<String>| {
+        let mut expected: Vec<String> = expected.iter().map(|e| token_kind(e)).collect();
+        expected.dedup();
+        // only the end is accepted after a complete formula
+        if expected.is_empty() && found.is_some() {
+            expected.push("end of formula".to_string());
+        }
+        FormulaParseError {
+            position: Some(position),
+            found: found.map(str::to_string),
+            expected,
+            message: None,
+        }
+    };
+    formula::FormulaParser::new()
+        .parse(input)
+        .map(|f| rename_time_placeholder(f, time_var))
+        .map_err(|e| match e {
+            ParseError::InvalidToken { location } => FormulaParseError {
+                position: Some(location),
+                found: input[location..].chars().next().map(|c| c.to_string()),
+                expected: vec![],
+                message: None,
+            },
+            ParseError::UnrecognizedEof { location, expected } => {
+                syntax_error(location, None, expected)
+            }
+            ParseError::UnrecognizedToken {
+                token: (start, Token(_, found), _),
+                expected,
+            } => syntax_error(start, Some(found), expected),
+            ParseError::ExtraToken {
+                token: (start, Token(_, found), _),
+            } => syntax_error(start, Some(found), vec![]),
+            ParseError::User { error } => FormulaParseError {
+                position: None,
+                found: None,
+                expected: vec![],
+                message: Some(error.to_string()),
+            },
+        })
+}
+
+/// Unescapes the contents of a quoted label: `\"` stands for `"` and `\\` for `\`.
+/// A backslash before any other character is kept.
+pub fn unescape_label(s: &str) -> String {
+    let mut label = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next @ ('"' | '\\'))) => {
+                label.push(next);
+                chars.next();
             }
-            node_attrs.insert(idx, attr_map);
+            (c, _) => label.push(c),
+        }
+    }
+    label
+}
+
+/// Escapes a label for writing it between quotes, the inverse of [`unescape_label`].
+pub fn escape_label(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Parses an integer literal with a radix prefix like `0x` or `0b`
+/// and an optional leading `-`. Returns `None` on overflow.
+pub fn parse_radix(s: &str, radix: u32) -> Option<i64> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let digits = &s[2..];
+    let value = i64::from_str_radix(digits, radix).ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Splits a `// key: value` comment token into a directive.
+pub fn parse_directive(token: &str) -> ParsedLine {
+    let (key, value) = token
+        .trim_start_matches('/')
+        .split_once(':')
+        .expect("directive tokens contain a colon");
+    ParsedLine::Directive(key.trim().to_string(), value.trim().to_string())
+}
+
+/// Parses the value of an `owner` node attribute, given as `owner[v]` or `owner=v`.
+/// `0` and `true` denote the reaching player (`NodeAttr::Owner(true)`),
+/// `1` and `false` its opponent; any other value is an error.
+pub fn owner_attr(value: &str) -> Result<NodeAttr, String> {
+    match value {
+        "0" | "true" => Ok(NodeAttr::Owner(true)),
+        "1" | "false" => Ok(NodeAttr::Owner(false)),
+        _ => Err(format!(
+            "invalid owner {}: expected 0, 1, true or false",
+            value
+        )),
+    }
+}
+
+/// Expands a node id range `from..to` like `v0..v3` into `v0, v1, v2, v3`.
+/// Both ids must consist of the same prefix followed by an integer suffix.
+/// Leading zeros in the suffix of `from` are kept, e.g. `v08..v10` gives `v08, v09, v10`.
+pub fn expand_id_range(from: &str, to: &str) -> Result<Vec<String>, String> {
+    fn split(id: &str) -> (&str, &str) {
+        let prefix = id.trim_end_matches(|c: char| c.is_ascii_digit());
+        (prefix, &id[prefix.len()..])
+    }
+    let (from_prefix, from_digits) = split(from);
+    let (to_prefix, to_digits) = split(to);
+    if from_prefix != to_prefix || from_digits.is_empty() || to_digits.is_empty() {
+        return Err(format!(
+            "invalid range {}..{}: ids must share a prefix and end in a number",
+            from, to
+        ));
+    }
+    let parse = |digits: &str| {
+        digits
+            .parse::<usize>()
+            .map_err(|e| format!("invalid range {}..{}: {}", from, to, e))
+    };
+    let (lo, hi) = (parse(from_digits)?, parse(to_digits)?);
+    if lo > hi {
+        return Err(format!("invalid range {}..{}: range is empty", from, to));
+    }
+    let width = if from_digits.starts_with('0') {
+        from_digits.len()
+    } else {
+        0
+    };
+    Ok((lo..=hi)
+        .map(|i| format!("{}{:0width$}", from_prefix, i, width = width))
+        .collect())
+}
+
+/// The variable that edge formulas use for time unless configured otherwise.
+pub const DEFAULT_TIME_VAR: &str = "t";
+
+/// The time variable of formulas desugared by the parsers (`(every m)`, `(0,3)` and `@[lo,hi]`),
+/// before it is renamed to the configured time variable.
+/// It is not a valid variable name, so it cannot clash with variables in the input.
+pub const TIME_VAR_PLACEHOLDER: &str = "#t";
+
+// replaces the placeholder time variable of desugared formulas by `time_var`
+fn rename_time_placeholder(formula: Formula, time_var: &str) -> Formula {
+    formula.substitute(TIME_VAR_PLACEHOLDER, &Expr::Var(time_var.to_string()))
+}
+
+/// Parser for temporal graphs in `.tg` format.
+///
+/// Edge formulas may only use the configured time variable (`t` by default)
+/// as free variable; edges using any other variable are rejected.
+/// The sugar forms `@[lo,hi]`, `(0,3)` and `(every m)` desugar to formulas over it.
+pub struct TemporalGraphParser {
+    /// The name of the time variable in edge formulas.
+    pub time_var: String,
+}
+
+impl Default for TemporalGraphParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TemporalGraphParser {
+    /// Creates a parser using the default time variable `t`.
+    pub fn new() -> Self {
+        Self::with_time_var(DEFAULT_TIME_VAR)
+    }
+
+    /// Creates a parser whose edge formulas use `time_var` as time variable.
+    pub fn with_time_var(time_var: &str) -> Self {
+        Self {
+            time_var: time_var.to_string(),
         }
     }
 
-    let node_count = next_idx;
+    /// Parses a temporal graph. Errors that are not syntax errors, like edges
+    /// between undeclared nodes, are reported as `ParseError::User`.
+    pub fn parse<'input>(
+        &self,
+        input: &'input str,
+    ) -> Result<TemporalGraph, ParseError<usize, Token<'input>, String>> {
+        self.parse_with_directives(input).map(|(graph, _)| graph)
+    }
+
+    /// Parses a temporal graph like [`TemporalGraphParser::parse`], also returning
+    /// the `// key: value` directives of the file (e.g. `time_bound`) in file order.
+    pub fn parse_with_directives<'input>(
+        &self,
+        input: &'input str,
+    ) -> Result<(TemporalGraph, Directives), ParseError<usize, Token<'input>, String>> {
+        let lines = tg_parser::LinesParser::new().parse(input)?;
+        let directives = lines
+            .iter()
+            .filter_map(|line| match line {
+                ParsedLine::Directive(key, value) => Some((key.clone(), value.clone())),
+                _ => None,
+            })
+            .collect();
+        let graph = temporal_graph_from_lines(lines, &self.time_var)
+            .map_err(|error| ParseError::User { error })?;
+        Ok((graph, directives))
+    }
 
-    let mut edges = Vec::new();
+    /// Parses a temporal graph line by line from `reader`, with the same result as
+    /// [`TemporalGraphParser::parse`], but without holding the whole input in memory:
+    /// only the parsed lines are kept until edge endpoints are resolved.
+    /// A block comment spanning several lines is buffered until it is closed,
+    /// and a statement continued on the next line until it is complete.
+    ///
+    /// Syntax and I/O errors are prefixed with the (1-based) line number.
+    pub fn parse_reader<R: BufRead>(&self, reader: R) -> Result<TemporalGraph, String> {
+        let parser = tg_parser::LinesParser::new();
+        let mut lines = Vec::new();
+        // text not parsed yet, starting at line `first`
+        let mut pending = String::new();
+        let mut first = 1;
+        let open_comment = |s: &str| s.rfind("/*").is_some_and(|i| !s[i..].contains("*/"));
 
-    for item in &edge_lines {
-        if let ParsedLine::Edge(from_id, to_id, formula) = item {
-            let from = *node_id_map.get(from_id).unwrap();
-            let to = *node_id_map.get(to_id).unwrap();
+        for (n, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| format!("line {}: {}", n + 1, e))?;
+            if pending.is_empty() {
+                first = n + 1;
+            }
+            pending.push_str(&line);
+            pending.push('\n');
+            if open_comment(&pending) {
+                continue;
+            }
+            match parser.parse(&pending) {
+                Ok(parsed) => lines.extend(parsed),
+                // the statement continues on the next line, e.g. an edge formula
+                Err(ParseError::UnrecognizedEof { .. }) => continue,
+                Err(e) => return Err(format!("line {}: {}", first, e)),
+            }
+            pending.clear();
+        }
+        if !pending.is_empty() {
+            let parsed = parser
+                .parse(&pending)
+                .map_err(|e| format!("line {}: {}", first, e))?;
+            lines.extend(parsed);
+        }
 
-            let formula = match formula {
-                Some(f) => f.clone(),
-                None => Formula::True,
-            };
+        temporal_graph_from_lines(lines, &self.time_var)
+    }
+}
 
-            edges.push(Edge::new(from, to, formula));
+/// Builds a temporal graph from parsed lines, with `time_var` as time variable.
+/// Constants from `#define` lines are substituted into every edge formula,
+/// wherever they are declared in the file, and desugared formulas are renamed
+/// from [`TIME_VAR_PLACEHOLDER`] to `time_var`.
+/// Fails if an edge refers to a node that is not declared,
+/// if a constant is defined twice or shadows `time_var`,
+/// or if its formula cannot be evaluated in terms of `time_var`
+/// (e.g. because it uses an undefined constant).
+pub fn temporal_graph_from_lines(
+    lines: Vec<ParsedLine>,
+    time_var: &str,
+) -> Result<TemporalGraph, String> {
+    let mut defines: Vec<(String, i64)> = Vec::new();
+    for item in &lines {
+        if let ParsedLine::Define(name, value) = item {
+            if name == time_var {
+                return Err(format!("cannot #define the time variable {}", name));
+            }
+            if defines.iter().any(|(n, _)| n == name) {
+                return Err(format!("constant {} is defined twice", name));
+            }
+            defines.push((name.clone(), *value));
         }
     }
 
-    TemporalGraph::new(node_count, node_id_map, node_attrs, edges)
+    // the builder resolves edge endpoints only once all nodes are known
+    let mut builder = TemporalGraphBuilder::new().time_var(time_var);
+    for item in lines {
+        builder = match item {
+            ParsedLine::Node(id, attrs) => builder.node_with_attrs(&id, &attrs),
+            ParsedLine::Edge(from, to, formula, weight) => {
+                let formula = defines
+                    .iter()
+                    .fold(formula.unwrap_or(Formula::True), |f, (name, value)| {
+                        f.substitute(name, &Expr::Const(*value))
+                    });
+                let formula = rename_time_placeholder(formula, time_var);
+                builder.weighted_edge(&from, &to, formula, weight)
+            }
+            ParsedLine::Define(..) | ParsedLine::Directive(..) | ParsedLine::Empty => builder,
+        };
+    }
+    builder.build()
 }
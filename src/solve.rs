@@ -0,0 +1,466 @@
+//! Solving punctual reachability games given as `.tg` text,
+//! the way the command line tool does.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+
+use lalrpop_util::ParseError;
+
+use crate::game::reachable_at;
+use crate::parser::tg_parser::NIDListParser;
+use crate::parser::{Directives, NodeAttr, TemporalGraphParser, owner_attr, parse_formula};
+use crate::temporal_graphs::TemporalGraph;
+
+/// The time bound used if neither the input nor the options specify one.
+pub const DEFAULT_TIME_BOUND: usize = 10;
+/// The target set used if neither the input nor the options specify one.
+pub const DEFAULT_TARGET_SET: &str = "v0";
+
+/// Options for [`solve`]. The time bound and target set are only used
+/// if the input itself does not specify them; the player is used in any case.
+#[derive(Debug, Clone, Default)]
+pub struct SolveOptions {
+    /// Target set as comma-separated node ids
+    pub target_set: Option<String>,
+    /// Time at which to reach the target set
+    pub time_bound: Option<usize>,
+    /// The reaching player (`true` as in `owner[0]`). Like `--player`, this takes
+    /// priority over the input; if not given, the input decides, or else `true`.
+    pub player: Option<bool>,
+    /// Path of the `.tg` file the input was read from, used to find its `.meta` file
+    pub input_path: Option<String>,
+}
+
+/// The outcome of [`solve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveResult {
+    /// The time bound that was used
+    pub k: usize,
+    /// Ids of the target nodes
+    pub target: HashSet<String>,
+    /// Ids of the nodes from which the target can be reached at time k
+    pub winning: HashSet<String>,
+}
+
+/// Errors reported by [`solve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// The temporal graph could not be parsed
+    GraphParse(String),
+    /// The target set could not be parsed
+    TargetParse(String),
+    /// The input parsed, but is inconsistent (e.g. an edge to an undeclared node)
+    Validation(String),
+}
+
+impl std::fmt::Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::GraphParse(e) => write!(f, "failed to parse temporal graph: {}", e),
+            SolveError::TargetParse(e) => write!(f, "failed to parse target set: {}", e),
+            SolveError::Validation(e) => write!(f, "invalid temporal graph: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// Parses a temporal graph, with the default time variable.
+pub fn parse_graph(input: &str) -> Result<TemporalGraph, SolveError> {
+    parse_graph_with_directives(input).map(|(graph, _)| graph)
+}
+
+/// Parses a temporal graph like [`parse_graph`], also returning its
+/// `// key: value` directives, see [`TemporalGraphParser::parse_with_directives`].
+pub fn parse_graph_with_directives(input: &str) -> Result<(TemporalGraph, Directives), SolveError> {
+    TemporalGraphParser::new()
+        .parse_with_directives(input)
+        .map_err(|e| match e {
+            ParseError::User { error } => SolveError::Validation(error),
+            e => SolveError::GraphParse(e.to_string()),
+        })
+}
+
+/// Parses a comma-separated list of node ids.
+pub fn parse_target_ids(target_set: &str) -> Result<HashSet<String>, SolveError> {
+    NIDListParser::new()
+        .parse(target_set)
+        .map(|v| v.into_iter().collect())
+        .map_err(|e| SolveError::TargetParse(e.to_string()))
+}
+
+/// Parses a predicate over node indices, given as a formula with at most one
+/// free variable that ranges over the indices, e.g. `(= (mod n 2) 0)` for even nodes.
+pub fn parse_index_predicate(expr: &str) -> Result<Box<dyn Fn(usize) -> bool>, SolveError> {
+    parse_formula(expr)
+        .map_err(|e| SolveError::TargetParse(e.to_string()))?
+        .as_closure()
+        .map_err(|e| SolveError::TargetParse(e.to_string()))
+}
+
+/// The contents of a `.meta` sidecar file: lines of the form `key: value`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Meta {
+    /// `time_bound: 10`
+    pub time_bound: Option<usize>,
+    /// `targets: s0,s1`
+    pub targets: Option<String>,
+    /// `player: 0`, the reaching player as in `owner[0]` (so `0` is player `true`)
+    pub player: Option<bool>,
+    /// `start: s0`
+    pub start: Option<String>,
+    /// Keys other than the above, which are ignored
+    pub unknown_keys: Vec<String>,
+    /// Why a `time_bound` or `player` value was ignored, for each invalid one
+    pub invalid_values: Vec<String>,
+}
+
+impl Meta {
+    /// Parses the contents of a `.meta` file.
+    /// Lines without `:` are skipped. As for directives, the first valid value of a key
+    /// is used; invalid `time_bound` and `player` values are reported in `invalid_values`.
+    pub fn parse(content: &str) -> Meta {
+        let mut meta = Meta::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "time_bound" => match value.parse() {
+                    Ok(time_bound) => meta.time_bound = meta.time_bound.or(Some(time_bound)),
+                    Err(_) => meta.invalid_values.push(format!(
+                        "invalid time bound {}: expected a non-negative integer",
+                        value
+                    )),
+                },
+                "targets" => meta.targets = meta.targets.or(Some(value.to_string())),
+                "player" => match parse_player(value) {
+                    Ok(player) => meta.player = meta.player.or(Some(player)),
+                    Err(e) => meta.invalid_values.push(e),
+                },
+                "start" => meta.start = meta.start.or(Some(value.to_string())),
+                key => meta.unknown_keys.push(key.to_string()),
+            }
+        }
+        meta
+    }
+}
+
+/// Parses a player like the value of `owner[...]`: `0` or `true` is player `true`,
+/// `1` or `false` is player `false`.
+pub fn parse_player(value: &str) -> Result<bool, String> {
+    owner_attr(value)
+        .map(|owner| owner == NodeAttr::Owner(true))
+        .map_err(|_| format!("invalid player {}: expected 0, 1, true or false", value))
+}
+
+/// Reads the `.meta` file next to a `.tg` file, if there is one.
+pub fn read_meta(file_path: &str) -> Option<Meta> {
+    // Convert .tg file to .meta file path
+    let meta_path = file_path.replace(".tg", ".meta");
+
+    let mut content = String::new();
+    File::open(&meta_path)
+        .ok()?
+        .read_to_string(&mut content)
+        .ok()?;
+    Some(Meta::parse(&content))
+}
+
+/// Reads the time bound from the `.meta` file next to a `.tg` file.
+pub fn read_time_bound_from_meta(file_path: &str) -> Option<usize> {
+    read_meta(file_path)?.time_bound
+}
+
+/// Reads the time bound from the first valid `// time_bound: ` directive.
+pub fn time_bound_directive(directives: &[(String, String)]) -> Option<usize> {
+    directives
+        .iter()
+        .filter(|(key, _)| key == "time_bound")
+        .find_map(|(_, value)| value.parse::<usize>().ok())
+}
+
+/// Reads the target set from the first `// targets: ` directive.
+pub fn targets_directive(directives: &[(String, String)]) -> Option<String> {
+    directives
+        .iter()
+        .find(|(key, _)| key == "targets")
+        .map(|(_, value)| value.clone())
+}
+
+/// Reads the reaching player from the first valid `// player: ` directive.
+pub fn player_directive(directives: &[(String, String)]) -> Option<bool> {
+    directives
+        .iter()
+        .filter(|(key, _)| key == "player")
+        .find_map(|(_, value)| parse_player(value).ok())
+}
+
+/// Determine time bound - priority order:
+/// 1. From a directive in the TG file (works with stdin)
+/// 2. From .meta file (only when file path available)
+/// 3. The given fallback
+pub fn determine_time_bound(
+    directives: &[(String, String)],
+    file_path: Option<&str>,
+    fallback: usize,
+) -> usize {
+    time_bound_directive(directives)
+        .or_else(|| match file_path {
+            Some(file_path) if file_path != "-" => read_time_bound_from_meta(file_path),
+            _ => None,
+        })
+        .unwrap_or(fallback)
+}
+
+/// Determine target set - priority order:
+/// 1. From a directive in the TG file (works with stdin)
+/// 2. From .meta file (only when file path available)
+/// 3. The given fallback
+pub fn determine_target_set(
+    directives: &[(String, String)],
+    file_path: Option<&str>,
+    fallback: &str,
+) -> String {
+    targets_directive(directives)
+        .or_else(|| match file_path {
+            Some(file_path) if file_path != "-" => read_meta(file_path)?.targets,
+            _ => None,
+        })
+        .unwrap_or(fallback.to_string())
+}
+
+/// Determine the reaching player - priority order:
+/// 1. From a directive in the TG file (works with stdin)
+/// 2. From .meta file (only when file path available)
+/// 3. The given fallback
+pub fn determine_player(
+    directives: &[(String, String)],
+    file_path: Option<&str>,
+    fallback: bool,
+) -> bool {
+    player_directive(directives)
+        .or_else(|| match file_path {
+            Some(file_path) if file_path != "-" => read_meta(file_path)?.player,
+            _ => None,
+        })
+        .unwrap_or(fallback)
+}
+
+/// Parses a temporal graph, determines the time bound, target set and reaching
+/// player like the command line tool, and solves the game.
+///
+/// # Arguments
+/// * `input` - the temporal graph in `.tg` format
+/// * `options` - fallbacks for the time bound and target set, and the input path
+pub fn solve(input: &str, options: SolveOptions) -> Result<SolveResult, SolveError> {
+    let (graph, directives) = parse_graph_with_directives(input)?;
+    let k = determine_time_bound(
+        &directives,
+        options.input_path.as_deref(),
+        options.time_bound.unwrap_or(DEFAULT_TIME_BOUND),
+    );
+    let target_set = determine_target_set(
+        &directives,
+        options.input_path.as_deref(),
+        options.target_set.as_deref().unwrap_or(DEFAULT_TARGET_SET),
+    );
+    let target = parse_target_ids(&target_set)?;
+    let player = match options.player {
+        Some(player) => player,
+        None => determine_player(&directives, options.input_path.as_deref(), true),
+    };
+
+    let target_at_k = graph.nodes_selected_from_ids(&target);
+    let wins_at = reachable_at(&graph, k, player, &target_at_k);
+
+    Ok(SolveResult {
+        k,
+        target: graph.ids_from_nodes_vec(&target_at_k),
+        winning: graph.ids_from_nodes_vec(&wins_at),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_STATE: &str = "
+        node s0: owner[0]
+        node s1: owner[0]
+        edge s0 -> s0
+        edge s1 -> s1
+        edge s0 -> s1: (>= t 5)
+    ";
+
+    fn ids(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_solve_with_options() {
+        let options = SolveOptions {
+            target_set: Some("s1".to_string()),
+            time_bound: Some(6),
+            ..Default::default()
+        };
+        let result = solve(TWO_STATE, options).expect("solve failed");
+        assert_eq!(result.k, 6);
+        assert_eq!(result.target, ids(&["s1"]));
+        assert_eq!(result.winning, ids(&["s0", "s1"]));
+
+        let options = SolveOptions {
+            target_set: Some("s1".to_string()),
+            time_bound: Some(5),
+            ..Default::default()
+        };
+        let result = solve(TWO_STATE, options).expect("solve failed");
+        assert_eq!(result.winning, ids(&["s1"]));
+    }
+
+    #[test]
+    fn test_solve_directives_take_priority() {
+        let input = format!("// time_bound: 6\n// targets: s1\n{}", TWO_STATE);
+        let options = SolveOptions {
+            target_set: Some("s0".to_string()),
+            time_bound: Some(2),
+            ..Default::default()
+        };
+        let result = solve(&input, options).expect("solve failed");
+        assert_eq!(result.k, 6);
+        assert_eq!(result.target, ids(&["s1"]));
+        assert_eq!(result.winning, ids(&["s0", "s1"]));
+    }
+
+    #[test]
+    fn test_solve_player() {
+        // player `false` owns neither node, so the opponent waits at s0
+        let options = || SolveOptions {
+            target_set: Some("s1".to_string()),
+            time_bound: Some(6),
+            ..Default::default()
+        };
+        let result = solve(
+            TWO_STATE,
+            SolveOptions {
+                player: Some(false),
+                ..options()
+            },
+        );
+        assert_eq!(result.expect("solve failed").winning, ids(&["s1"]));
+
+        let input = format!("// player: 1\n{}", TWO_STATE);
+        let result = solve(&input, options()).expect("solve failed");
+        assert_eq!(result.winning, ids(&["s1"]));
+
+        // the option takes priority over the directive, like --player
+        let result = solve(
+            &input,
+            SolveOptions {
+                player: Some(true),
+                ..options()
+            },
+        );
+        assert_eq!(result.expect("solve failed").winning, ids(&["s0", "s1"]));
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        assert!(matches!(
+            solve("node s0\nedge s0 ->", SolveOptions::default()),
+            Err(SolveError::GraphParse(_))
+        ));
+        assert!(matches!(
+            solve("node s0\nedge s0 -> s1", SolveOptions::default()),
+            Err(SolveError::Validation(_))
+        ));
+        let options = SolveOptions {
+            target_set: Some("s0,,s1".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            solve(TWO_STATE, options),
+            Err(SolveError::TargetParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_meta() {
+        let meta =
+            Meta::parse("time_bound: 7\ntargets: s0, s1\nplayer: 1\nstart: s0\ncolor: red\n");
+        assert_eq!(
+            meta,
+            Meta {
+                time_bound: Some(7),
+                targets: Some("s0, s1".to_string()),
+                player: Some(false),
+                start: Some("s0".to_string()),
+                unknown_keys: vec!["color".to_string()],
+                invalid_values: vec![],
+            }
+        );
+
+        let meta = Meta::parse("player: 0\ntime_bound: soon\n");
+        assert_eq!(
+            meta,
+            Meta {
+                player: Some(true),
+                invalid_values: vec![
+                    "invalid time bound soon: expected a non-negative integer".to_string()
+                ],
+                ..Default::default()
+            }
+        );
+
+        // the first valid value is used, as for directives
+        let meta =
+            Meta::parse("time_bound: -1\ntime_bound: 4\ntime_bound: 9\nstart: s0\nstart: s1\n");
+        assert_eq!(meta.time_bound, Some(4));
+        assert_eq!(meta.start.as_deref(), Some("s0"));
+        assert_eq!(meta.invalid_values.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_player() {
+        assert_eq!(parse_player("0"), Ok(true));
+        assert_eq!(parse_player("true"), Ok(true));
+        assert_eq!(parse_player("1"), Ok(false));
+        assert_eq!(parse_player("false"), Ok(false));
+        for value in ["2", "7", "-1", "player0", ""] {
+            assert!(parse_player(value).is_err(), "{}", value);
+        }
+
+        let meta = Meta::parse("player: 2\nplayer: 1\n");
+        assert_eq!(meta.player, Some(false));
+        assert_eq!(
+            meta.invalid_values,
+            ["invalid player 2: expected 0, 1, true or false"]
+        );
+        assert_eq!(
+            player_directive(&[
+                ("player".to_string(), "7".to_string()),
+                ("player".to_string(), "true".to_string()),
+            ]),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_read_meta() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/batch/b.tg");
+        let meta = read_meta(path).expect("missing .meta file");
+        assert_eq!(meta.time_bound, Some(3));
+        assert_eq!(meta.targets, None);
+        assert_eq!(read_time_bound_from_meta(path), Some(3));
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/meta.tg");
+        let meta = read_meta(path).expect("missing .meta file");
+        assert_eq!(meta.time_bound, Some(6));
+        assert_eq!(meta.targets.as_deref(), Some("s1"));
+        assert_eq!(meta.player, Some(true));
+        assert_eq!(meta.start.as_deref(), Some("s0"));
+        assert!(meta.unknown_keys.is_empty());
+    }
+}
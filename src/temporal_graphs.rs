@@ -1,45 +1,170 @@
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
 
-use crate::{formulae::Formula, parser::NodeAttr};
+use crate::{
+    formulae::{Formula, lcm},
+    parser::{NodeAttr, escape_label},
+};
 
 #[allow(dead_code)]
 pub type Node = usize;
 
+/// A compiled availability formula, shared between edges with the same formula.
+pub type Availability = Arc<dyn Fn(usize) -> bool + 'static>;
+
+// compiled formulas by formula, so that identical formulas are compiled once
+type AvailabilityCache = HashMap<Formula, Availability>;
+
+/// Cloning an edge shares its compiled availability closure.
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct Edge {
     source: Node,
     target: Node,
     formula: Formula,
-    available_at: Box<dyn Fn(usize) -> bool + 'static>,
+    available_at: Availability,
+    // `lo..=hi` (unbounded if `None`) if the formula is interval-shaped,
+    // see `Formula::as_interval`; checked instead of `available_at`
+    interval: Option<(Option<usize>, Option<usize>)>,
+    // `(modulus, residue)` pairs if the formula is a union of arithmetic progressions,
+    // see `Formula::as_ap_union`; checked instead of `available_at`
+    aps: Option<Vec<(usize, usize)>>,
+    weight: i64,
 }
 
 impl Edge {
     pub fn new(source: Node, target: Node, formula: Formula) -> Self {
-        let available_at = match formula.clone().as_closure() {
-            Ok(f) => f,
-            Err(_) => Box::new(|_| false),
+        Self::new_cached(source, target, formula, &mut AvailabilityCache::new())
+    }
+
+    // `new`, reusing the compiled formula from `cache` if there is one
+    fn new_cached(
+        source: Node,
+        target: Node,
+        formula: Formula,
+        cache: &mut AvailabilityCache,
+    ) -> Self {
+        let available_at = compile_cached(&formula, cache).unwrap_or_else(|_| Arc::new(|_| false));
+        let free_vars = formula.free_variables();
+        let (interval, aps) = match free_vars.len() {
+            0 => (formula.as_interval(""), None),
+            1 => {
+                let var = free_vars.into_iter().next().unwrap();
+                (formula.as_interval(var), formula.as_ap_union(var))
+            }
+            _ => (None, None),
         };
         Self {
             source,
             target,
             formula,
             available_at,
+            interval,
+            aps,
+            weight: 0,
         }
     }
+    /// Creates an edge whose formula uses `time_var` as time variable.
+    /// Unlike `new`, this fails if the formula has a free variable other than
+    /// `time_var`, or cannot be turned into a closure.
+    pub fn with_time_var(
+        source: Node,
+        target: Node,
+        formula: Formula,
+        time_var: &str,
+    ) -> Result<Self, String> {
+        Self::with_time_var_cached(
+            source,
+            target,
+            formula,
+            time_var,
+            &mut AvailabilityCache::new(),
+        )
+    }
+
+    // `with_time_var`, reusing the compiled formula from `cache` if there is one
+    fn with_time_var_cached(
+        source: Node,
+        target: Node,
+        formula: Formula,
+        time_var: &str,
+        cache: &mut AvailabilityCache,
+    ) -> Result<Self, String> {
+        if let Some(var) = formula
+            .free_variables()
+            .into_iter()
+            .find(|v| *v != time_var)
+        {
+            return Err(format!(
+                "formula uses variable '{}' but the time variable is '{}'",
+                var, time_var
+            ));
+        }
+        let available_at = compile_cached(&formula, cache)?;
+        let interval = formula.as_interval(time_var);
+        let aps = formula.as_ap_union(time_var);
+        Ok(Self {
+            source,
+            target,
+            formula,
+            available_at,
+            interval,
+            aps,
+            weight: 0,
+        })
+    }
+    /// Replaces the availability check by a constant `true`, keeping the formula.
+    /// Used for edges whose formula is known to hold at every relevant time.
+    fn always_available(mut self) -> Self {
+        self.available_at = Arc::new(|_| true);
+        self.interval = Some((None, None));
+        self
+    }
+    /// Sets the weight (energy cost) of the edge, which is 0 by default.
+    pub fn with_weight(mut self, weight: i64) -> Self {
+        self.weight = weight;
+        self
+    }
     pub fn new_simple(source: Node, target: Node) -> Self {
         Self::new(source, target, Formula::True)
     }
 
-    fn source(&self) -> &Node {
+    pub fn source(&self) -> &Node {
         &self.source
     }
-    fn target(&self) -> &Node {
+    pub fn target(&self) -> &Node {
         &self.target
     }
+    pub fn formula(&self) -> &Formula {
+        &self.formula
+    }
+    pub fn weight(&self) -> i64 {
+        self.weight
+    }
     pub fn is_available(&self, time: usize) -> bool {
-        (self.available_at)(time)
+        match (&self.interval, &self.aps) {
+            (Some((lo, hi)), _) => lo.is_none_or(|lo| time >= lo) && hi.is_none_or(|hi| time <= hi),
+            (None, Some(aps)) => aps.iter().any(|&(m, r)| time % m == r),
+            (None, None) => (self.available_at)(time),
+        }
+    }
+}
+
+// compiles `formula` with `as_closure`, or takes the closure compiled for an equal formula
+fn compile_cached(
+    formula: &Formula,
+    cache: &mut AvailabilityCache,
+) -> Result<Availability, &'static str> {
+    if let Some(available_at) = cache.get(formula) {
+        return Ok(Arc::clone(available_at));
     }
+    let available_at: Availability = Arc::from(formula.clone().as_closure()?);
+    cache.insert(formula.clone(), Arc::clone(&available_at));
+    Ok(available_at)
 }
+
 // to print Edges : skip available_at
 impl std::fmt::Debug for Edge {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -47,14 +172,44 @@ impl std::fmt::Debug for Edge {
             .field("source", &self.source)
             .field("target", &self.target)
             .field("formula", &self.formula)
+            .field("weight", &self.weight)
             .finish()
     }
 }
 
+/// Summary statistics of a temporal graph, see [`TemporalGraph::stats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphStats {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// Nodes declared `owner[0]` in the input format (`NodeAttr::Owner(true)`)
+    pub player0_nodes: usize,
+    /// All other nodes
+    pub player1_nodes: usize,
+    /// The largest number of outgoing edges of a node, regardless of availability
+    pub max_out_degree: usize,
+    /// Edges whose formula is not `Formula::True`
+    pub constrained_edges: usize,
+}
+
+impl std::fmt::Display for GraphStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "nodes: {}", self.node_count)?;
+        writeln!(f, "edges: {}", self.edge_count)?;
+        writeln!(f, "player 0 nodes: {}", self.player0_nodes)?;
+        writeln!(f, "player 1 nodes: {}", self.player1_nodes)?;
+        writeln!(f, "max out-degree: {}", self.max_out_degree)?;
+        write!(f, "constrained edges: {}", self.constrained_edges)
+    }
+}
+
 /// A temporal graph is parameterized by the type of TemporalEdge.
 /// Stores outgoing edges for each node for efficient access.
 /// Stores outgoing edges for each node for efficient access.
-#[derive(Debug)]
+///
+/// Cloning a graph shares the compiled availability closures of its edges
+/// (see [`Edge`]) instead of recompiling them, so it is cheap.
+#[derive(Debug, Clone)]
 pub struct TemporalGraph {
     /// The number of nodes in the graph.
     pub node_count: usize,
@@ -96,20 +251,315 @@ impl TemporalGraph {
         self.edges.get(&from).into_iter().flat_map(|v| v.iter())
     }
 
+    /// Returns the number of edges, regardless of availability.
+    /// Takes time linear in the number of nodes with outgoing edges, not in the number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.edges.values().map(Vec::len).sum()
+    }
+
+    /// Returns the number of outgoing edges of the given node, regardless of availability.
+    pub fn out_degree(&self, n: Node) -> usize {
+        self.edges.get(&n).map_or(0, Vec::len)
+    }
+
     /// Returns an iterator over all outgoing edges from the given node that are available at the given time.
     pub fn edges_from_at(&self, from: Node, time: usize) -> impl Iterator<Item = &Edge> {
         self.edges_from(from).filter(move |e| e.is_available(time))
     }
 
+    /// Returns an iterator over all outgoing edges from the given node, each paired with
+    /// every time in `lo..=hi` at which it is available.
+    pub fn edges_from_in_window(
+        &self,
+        from: Node,
+        lo: usize,
+        hi: usize,
+    ) -> impl Iterator<Item = (&Edge, usize)> {
+        self.edges_from(from).flat_map(move |e| {
+            (lo..=hi)
+                .filter(|&t| e.is_available(t))
+                .map(move |t| (e, t))
+        })
+    }
+
+    /// Returns an iterator over the edges whose formula is not `Formula::True`, as
+    /// `(from, to, formula)` triples grouped by source node in increasing order.
+    pub fn constrained_edges(&self) -> impl Iterator<Item = (Node, Node, &Formula)> {
+        self.nodes()
+            .flat_map(move |n| self.edges_from(n))
+            .filter(|e| *e.formula() != Formula::True)
+            .map(|e| (*e.source(), *e.target(), e.formula()))
+    }
+
+    /// Returns an iterator over all `(from, to, time)` triples such that an edge from `from`
+    /// to `to` is available at `time`, for times in `0..=k`: the time-expanded graph,
+    /// without materializing it. Triples are grouped by source node, in increasing order,
+    /// with one triple per parallel edge.
+    pub fn timed_edges(&self, k: usize) -> impl Iterator<Item = (Node, Node, usize)> {
+        self.nodes().flat_map(move |n| {
+            self.edges_from_in_window(n, 0, k)
+                .map(|(e, t)| (*e.source(), *e.target(), t))
+        })
+    }
+
+    /// Returns the times in `0..=k` at which some edge from `from` to `to` is available,
+    /// in increasing order; empty if there is no such edge.
+    pub fn availability_trace(&self, from: Node, to: Node, k: usize) -> Vec<usize> {
+        (0..=k)
+            .filter(|&t| {
+                self.edges_from(from)
+                    .any(|e| *e.target() == to && e.is_available(t))
+            })
+            .collect()
+    }
+
     /// Returns an iterator over all node indices in the graph.
     pub fn nodes(&self) -> impl Iterator<Item = Node> {
         0..self.node_count
     }
 
+    /// Returns an iterator over the targets of all outgoing edges, regardless of availability.
+    pub fn successors(&self, from: Node) -> impl Iterator<Item = Node> {
+        self.edges_from(from).map(|e| *e.target())
+    }
+
     pub fn successors_at(&self, from: Node, time: usize) -> impl Iterator<Item = Node> {
         self.edges_from_at(from, time).map(|e| *e.target())
     }
 
+    /// Returns the successors of `from` at `time` as a mask with one entry per node,
+    /// in the representation of node sets used by the solver (see [`crate::bitset`]).
+    pub fn successors_mask_at(&self, from: Node, time: usize) -> Vec<bool> {
+        let mut mask = vec![false; self.node_count];
+        for succ in self.successors_at(from, time) {
+            mask[succ] = true;
+        }
+        mask
+    }
+
+    /// Evaluates edge availability once for all times `0..k`.
+    ///
+    /// Entry `[i][n]` of the result holds the successors of node `n` at time `i`,
+    /// as returned by `successors_at(n, i)`.
+    pub fn precompute_successors(&self, k: usize) -> Vec<Vec<Vec<Node>>> {
+        (0..k)
+            .map(|i| {
+                self.nodes()
+                    .map(|n| self.successors_at(n, i).collect())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the predecessor index: entry `n` lists the sources of all edges into `n`,
+    /// regardless of availability (with repetitions for parallel edges).
+    pub fn predecessors(&self) -> Vec<Vec<Node>> {
+        let mut predecessors = vec![Vec::new(); self.node_count];
+        for e in self.edges() {
+            predecessors[*e.target()].push(*e.source());
+        }
+        predecessors
+    }
+
+    /// Returns the nodes that are likely modelling mistakes, in increasing order:
+    /// sinks without an available outgoing edge at any time in `0..=k`,
+    /// and nodes without any incoming edge (a self-loop counts as incoming).
+    pub fn dead_nodes(&self, k: usize) -> Vec<Node> {
+        let predecessors = self.predecessors();
+        self.nodes()
+            .filter(|&n| {
+                predecessors[n].is_empty() || self.edges_from_in_window(n, 0, k).next().is_none()
+            })
+            .collect()
+    }
+
+    /// Returns the adjacency matrix of the edges available at the given time:
+    /// entry `[n][m]` is true if some edge from `n` to `m` is available.
+    pub fn adjacency_at(&self, time: usize) -> Vec<Vec<bool>> {
+        let mut matrix = vec![vec![false; self.node_count]; self.node_count];
+        for node in self.nodes() {
+            for succ in self.successors_at(node, time) {
+                matrix[node][succ] = true;
+            }
+        }
+        matrix
+    }
+
+    /// Returns true if no edge availability depends on time,
+    /// i.e. every edge formula is `Formula::True`.
+    pub fn is_time_invariant(&self) -> bool {
+        self.edges().all(|e| *e.formula() == Formula::True)
+    }
+
+    /// Returns, for each node, whether it has a self-loop that is always available,
+    /// i.e. an edge to itself with formula `Formula::True`.
+    pub fn always_self_loops(&self) -> Vec<bool> {
+        let mut loops = vec![false; self.node_count];
+        for e in self.edges() {
+            if e.source() == e.target() && *e.formula() == Formula::True {
+                loops[*e.source()] = true;
+            }
+        }
+        loops
+    }
+
+    /// Returns a copy of the graph without self-loops, for solving the variant in which
+    /// every player has to move to another node at each step.
+    ///
+    /// Waiting at a node is then only possible along longer cycles. A node owned by
+    /// the reaching player that could only wait is stuck, and losing for it; an opponent
+    /// node that could only wait is stuck too, which the default
+    /// [`DeadlockPolicy`](crate::game::DeadlockPolicy) still counts as a win for the
+    /// opponent. An opponent node with other available edges, however, has to take one.
+    pub fn without_self_loops(&self) -> TemporalGraph {
+        let edges = self
+            .edges()
+            .filter(|e| e.source() != e.target())
+            .cloned()
+            .collect();
+        TemporalGraph::new(
+            self.node_count,
+            self.node_id_map.clone(),
+            self.node_attrs.clone(),
+            edges,
+        )
+    }
+
+    /// Returns the global period of edge availability, if every edge formula is periodic.
+    ///
+    /// This is the least common multiple of the periods of all edge formulas
+    /// (see [`Formula::period`]); a graph without edges has period 1.
+    pub fn availability_period(&self) -> Option<usize> {
+        self.edges()
+            .try_fold(1, |acc, e| e.formula().period().map(|p| lcm(acc, p)))
+    }
+
+    /// Returns the time from which on no edge's availability changes any more,
+    /// if every edge formula is interval-shaped (see [`Formula::as_interval`]):
+    /// an edge available for times `lo..=hi` changes at `lo` and at `hi + 1`.
+    /// Steps of the backward induction at this time or later are all identical.
+    ///
+    /// Returns `None` if some edge formula is not an interval, e.g. a periodic one.
+    /// A graph without edges, or with always available edges only, gives `Some(0)`.
+    pub fn max_meaningful_time(&self) -> Option<usize> {
+        self.edges().try_fold(0, |acc, e| {
+            let (lo, hi) = e.interval?;
+            Some(acc.max(lo.unwrap_or(0)).max(hi.map_or(0, |hi| hi + 1)))
+        })
+    }
+
+    /// Returns a hash of the graph's structure that does not depend on the order of
+    /// its declarations: nodes by id with owner and label, and edges by endpoint ids
+    /// with weight and formula (after [`Formula::simplify`]).
+    ///
+    /// Equal graphs hash equally, whatever order their `.tg` lines are in;
+    /// graphs that differ only by a renaming of node ids generally do not.
+    /// The value is stable across runs, but not across versions of this crate.
+    pub fn canonical_hash(&self) -> u64 {
+        let mut ids = vec![""; self.node_count];
+        for (id, &idx) in &self.node_id_map {
+            ids[idx] = id.as_str();
+        }
+
+        let mut nodes: Vec<(&str, Option<bool>, Option<&str>)> = self
+            .nodes()
+            .map(|n| {
+                let owner = match self.node_attrs.get(&n).and_then(|a| a.get("owner")) {
+                    Some(NodeAttr::Owner(owner)) => Some(*owner),
+                    _ => None,
+                };
+                (ids[n], owner, self.label(n))
+            })
+            .collect();
+        nodes.sort();
+
+        let mut edges: Vec<(&str, &str, i64, Formula)> = self
+            .edges()
+            .map(|e| {
+                let formula = e.formula().clone().simplify();
+                (ids[*e.source()], ids[*e.target()], e.weight(), formula)
+            })
+            .collect();
+        edges.sort_by(|a, b| {
+            (a.0, a.1, a.2)
+                .cmp(&(b.0, b.1, b.2))
+                .then_with(|| a.3.to_string().cmp(&b.3.to_string()))
+        });
+
+        let mut hasher = DefaultHasher::new();
+        nodes.hash(&mut hasher);
+        edges.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Checks the consistency of a graph assembled by hand with [`TemporalGraph::new`],
+    /// which graphs from the parser or the builder always satisfy: every index below
+    /// `node_count` has exactly one node id, and edges and node attributes only refer
+    /// to such indices. Reports every problem found, one per line.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+        let mut ids: Vec<Vec<&str>> = vec![Vec::new(); self.node_count];
+        let mut sorted_ids: Vec<(&String, &Node)> = self.node_id_map.iter().collect();
+        sorted_ids.sort_by(|a, b| natural_cmp(a.0, b.0));
+        for (id, &idx) in sorted_ids {
+            match ids.get_mut(idx) {
+                Some(ids) => ids.push(id),
+                None => problems.push(format!(
+                    "node {} has index {}, but the graph has {} nodes",
+                    id, idx, self.node_count
+                )),
+            }
+        }
+        for (idx, ids) in ids.iter().enumerate() {
+            match ids.len() {
+                0 => problems.push(format!("node index {} has no id", idx)),
+                1 => {}
+                _ => problems.push(format!("nodes {} share index {}", ids.join(", "), idx)),
+            }
+        }
+        let mut attr_nodes: Vec<Node> = self.node_attrs.keys().copied().collect();
+        attr_nodes.sort();
+        for idx in attr_nodes.into_iter().filter(|&idx| idx >= self.node_count) {
+            problems.push(format!(
+                "attributes given for node index {}, but the graph has {} nodes",
+                idx, self.node_count
+            ));
+        }
+        let mut edge_nodes: Vec<(Node, Node)> = self
+            .edges()
+            .map(|e| (*e.source(), *e.target()))
+            .filter(|&(from, to)| from >= self.node_count || to >= self.node_count)
+            .collect();
+        edge_nodes.sort();
+        for (from, to) in edge_nodes {
+            problems.push(format!(
+                "edge {} -> {} refers to a node index outside the graph's {} nodes",
+                from, to, self.node_count
+            ));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("\n"))
+        }
+    }
+
+    /// Computes summary statistics of the graph, without considering edge availability.
+    pub fn stats(&self) -> GraphStats {
+        let player0_nodes = self.node_ownership().iter().filter(|&&o| o).count();
+        GraphStats {
+            node_count: self.node_count,
+            edge_count: self.edge_count(),
+            player0_nodes,
+            player1_nodes: self.node_count - player0_nodes,
+            max_out_degree: self.nodes().map(|n| self.out_degree(n)).max().unwrap_or(0),
+            constrained_edges: self
+                .edges()
+                .filter(|e| *e.formula() != Formula::True)
+                .count(),
+        }
+    }
+
     pub fn node_ownership(&self) -> Vec<bool> {
         let mut player_one_nodes = vec![false; self.node_count];
         for node in self.nodes() {
@@ -126,20 +576,72 @@ impl TemporalGraph {
         player_one_nodes
     }
 
+    /// Returns the label of a node, if it has one.
+    pub fn label(&self, node: Node) -> Option<&str> {
+        match self.node_attrs.get(&node)?.get("label")? {
+            NodeAttr::Label(label) => Some(label),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the nodes whose entry in `node_ownership` equals `player`.
+    pub fn nodes_of(&self, player: bool) -> impl Iterator<Item = Node> {
+        let owner = self.node_ownership();
+        self.nodes().filter(move |&n| owner[n] == player)
+    }
+
+    /// Returns a vector of bools of length node_count selecting every node.
+    pub fn all_nodes_selected(&self) -> Vec<bool> {
+        vec![true; self.node_count]
+    }
+
+    /// Returns a vector of bools of length node_count selecting the nodes
+    /// whose index satisfies `pred`.
+    pub fn nodes_matching_index<F: Fn(usize) -> bool>(&self, pred: F) -> Vec<bool> {
+        self.nodes().map(pred).collect()
+    }
+
+    /// Returns a vector of bools of length node_count selecting the nodes
+    /// whose label satisfies `pred`. Nodes without a label are not selected.
+    pub fn nodes_matching_label<F: Fn(&str) -> bool>(&self, pred: F) -> Vec<bool> {
+        self.nodes()
+            .map(|n| self.label(n).is_some_and(&pred))
+            .collect()
+    }
+
     /// Given a set of node id strings, returns a vector of bools of length node_count.
     /// For each string, if node_id_map gives a Node with index n, then the returned vector is true at position n.
     pub fn nodes_selected_from_ids(&self, ids: &HashSet<String>) -> Vec<bool> {
         let mut selected = vec![false; self.node_count];
         for id in ids {
-            if let Some(&n) = self.node_id_map.get(id) {
-                if n < self.node_count {
-                    selected[n] = true;
-                }
+            if let Some(&n) = self.node_id_map.get(id)
+                && n < self.node_count
+            {
+                selected[n] = true;
             }
         }
         selected
     }
 
+    /// Like `nodes_selected_from_ids`, but fails with the (sorted) list of ids
+    /// that are not nodes of the graph.
+    pub fn nodes_selected_from_ids_checked(
+        &self,
+        ids: &HashSet<String>,
+    ) -> Result<Vec<bool>, Vec<String>> {
+        let mut unknown: Vec<String> = ids
+            .iter()
+            .filter(|id| !self.node_id_map.contains_key(*id))
+            .cloned()
+            .collect();
+        if unknown.is_empty() {
+            Ok(self.nodes_selected_from_ids(ids))
+        } else {
+            unknown.sort();
+            Err(unknown)
+        }
+    }
+
     // id strings for vector of nodes
     pub fn ids_from_nodes_vec(&self, v: &[bool]) -> HashSet<String> {
         let mut ids = HashSet::<String>::new();
@@ -150,6 +652,483 @@ impl TemporalGraph {
         }
         ids
     }
+
+    /// Solves the punctual reachability game for `player` with the target and the
+    /// winning region given as node ids, see [`crate::game::reachable_at`].
+    /// Ids that are not nodes of the graph are ignored.
+    pub fn reachable_from_ids(
+        &self,
+        k: usize,
+        player: bool,
+        target_ids: &HashSet<String>,
+    ) -> HashSet<String> {
+        let target = self.nodes_selected_from_ids(target_ids);
+        self.ids_from_nodes_vec(&crate::game::reachable_at(self, k, player, &target))
+    }
+
+    /// Exports the graph as GraphML.
+    ///
+    /// Nodes carry `owner` (0 or 1, as in `owner[0]` of the input format) and
+    /// `label` data, edges carry their `formula` in the syntax of the formula parser.
+    pub fn to_graphml(&self) -> String {
+        let mut ids = vec![String::new(); self.node_count];
+        for (id, &idx) in &self.node_id_map {
+            ids[idx] = xml_escape(id);
+        }
+        let owner = self.node_ownership();
+
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        xml.push_str("  <key id=\"owner\" for=\"node\" attr.name=\"owner\" attr.type=\"int\"/>\n");
+        xml.push_str(
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        );
+        xml.push_str(
+            "  <key id=\"formula\" for=\"edge\" attr.name=\"formula\" attr.type=\"string\"/>\n",
+        );
+        xml.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+        for node in self.nodes() {
+            xml.push_str(&format!("    <node id=\"{}\">\n", ids[node]));
+            xml.push_str(&format!(
+                "      <data key=\"owner\">{}</data>\n",
+                if owner[node] { 0 } else { 1 }
+            ));
+            if let Some(label) = self.label(node) {
+                xml.push_str(&format!(
+                    "      <data key=\"label\">{}</data>\n",
+                    xml_escape(label)
+                ));
+            }
+            xml.push_str("    </node>\n");
+        }
+        for node in self.nodes() {
+            for edge in self.edges_from(node) {
+                xml.push_str(&format!(
+                    "    <edge source=\"{}\" target=\"{}\">\n",
+                    ids[*edge.source()],
+                    ids[*edge.target()]
+                ));
+                xml.push_str(&format!(
+                    "      <data key=\"formula\">{}</data>\n",
+                    xml_escape(&edge.formula().to_string())
+                ));
+                xml.push_str("    </edge>\n");
+            }
+        }
+        xml.push_str("  </graph>\n");
+        xml.push_str("</graphml>\n");
+        xml
+    }
+
+    /// Writes the graph in `.tg` format, re-parseable by `TemporalGraphParser`
+    /// (with the time variable the edge formulas use).
+    ///
+    /// Nodes are written in index order with their owner and label, followed by the
+    /// edges ordered by source node. Always available edges are written without a formula.
+    pub fn to_tg(&self) -> String {
+        let mut ids = vec![String::new(); self.node_count];
+        for (id, &idx) in &self.node_id_map {
+            ids[idx] = id.clone();
+        }
+
+        let mut tg = String::new();
+        for node in self.nodes() {
+            let mut attrs = Vec::new();
+            if let Some(NodeAttr::Owner(owner)) =
+                self.node_attrs.get(&node).and_then(|a| a.get("owner"))
+            {
+                attrs.push(format!("owner[{}]", if *owner { 0 } else { 1 }));
+            }
+            if let Some(label) = self.label(node) {
+                attrs.push(format!("label[\"{}\"]", escape_label(label)));
+            }
+            match attrs.is_empty() {
+                true => tg.push_str(&format!("node {}\n", ids[node])),
+                false => tg.push_str(&format!("node {}: {}\n", ids[node], attrs.join(", "))),
+            }
+        }
+        for node in self.nodes() {
+            for edge in self.edges_from(node) {
+                tg.push_str(&format!("edge {} -> {}", ids[node], ids[*edge.target()]));
+                match edge.formula() {
+                    Formula::True => {}
+                    // a bare `false` would be read as a node id
+                    Formula::False => tg.push_str(": (not true)"),
+                    formula => tg.push_str(&format!(": {}", formula)),
+                }
+                if edge.weight() != 0 {
+                    tg.push_str(&format!(" [w={}]", edge.weight()));
+                }
+                tg.push('\n');
+            }
+        }
+        tg
+    }
+
+    /// Id strings for a vector of nodes, in the order of [`natural_cmp`],
+    /// for deterministic output.
+    pub fn sorted_ids_from_nodes_vec(&self, v: &[bool]) -> Vec<String> {
+        let mut ids: Vec<String> = self.ids_from_nodes_vec(v).into_iter().collect();
+        ids.sort_by(|a, b| natural_cmp(a, b));
+        ids
+    }
+
+    /// Renames node ids according to `mapping`; ids not in the mapping are kept.
+    ///
+    /// Fails, leaving the graph unchanged, if the mapping renames an unknown id
+    /// or if two nodes would end up with the same id.
+    pub fn relabel(&mut self, mapping: &HashMap<String, String>) -> Result<(), String> {
+        let mut unknown: Vec<&String> = mapping
+            .keys()
+            .filter(|id| !self.node_id_map.contains_key(*id))
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            return Err(format!(
+                "cannot rename unknown node ids: {}",
+                unknown
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        let mut node_id_map = HashMap::new();
+        for (id, &idx) in &self.node_id_map {
+            let new_id = mapping.get(id).unwrap_or(id);
+            if node_id_map.insert(new_id.clone(), idx).is_some() {
+                return Err(format!("renaming results in duplicate node id {}", new_id));
+            }
+        }
+        self.node_id_map = node_id_map;
+        Ok(())
+    }
+
+    /// Returns the subgraph induced by the nodes in `keep`.
+    ///
+    /// Kept nodes are renumbered compactly in their original order, keeping their ids
+    /// and attributes. Edges with a dropped endpoint are omitted; the other edges are
+    /// kept as they are, sharing their compiled availability with this graph.
+    pub fn subgraph(&self, keep: &[bool]) -> TemporalGraph {
+        let kept = |n: Node| keep.get(n).copied().unwrap_or(false);
+        let mut new_index: HashMap<Node, Node> = HashMap::new();
+        for node in self.nodes().filter(|&n| kept(n)) {
+            new_index.insert(node, new_index.len());
+        }
+
+        let node_id_map = self
+            .node_id_map
+            .iter()
+            .filter_map(|(id, n)| new_index.get(n).map(|&m| (id.clone(), m)))
+            .collect();
+        let node_attrs = self
+            .node_attrs
+            .iter()
+            .filter_map(|(n, attrs)| new_index.get(n).map(|&m| (m, attrs.clone())))
+            .collect();
+        let edges = self
+            .edges()
+            .filter_map(|e| {
+                let mut edge = e.clone();
+                edge.source = *new_index.get(e.source())?;
+                edge.target = *new_index.get(e.target())?;
+                Some(edge)
+            })
+            .collect();
+
+        TemporalGraph::new(new_index.len(), node_id_map, node_attrs, edges)
+    }
+
+    /// Returns the disjoint union of two graphs.
+    ///
+    /// The nodes of `other` are renumbered after the nodes of `self`, keeping their
+    /// attributes and edges. If `prefix` is given, it is prepended to the ids of `other`.
+    /// Fails if an id of `other` (after prefixing) is already an id of `self`.
+    pub fn merge(
+        self,
+        other: TemporalGraph,
+        prefix: Option<&str>,
+    ) -> Result<TemporalGraph, String> {
+        let offset = self.node_count;
+        let mut node_id_map = self.node_id_map;
+        for (id, idx) in other.node_id_map {
+            let id = format!("{}{}", prefix.unwrap_or(""), id);
+            if node_id_map.contains_key(&id) {
+                return Err(format!("duplicate node id {} when merging graphs", id));
+            }
+            node_id_map.insert(id, idx + offset);
+        }
+
+        let mut node_attrs = self.node_attrs;
+        node_attrs.extend(
+            other
+                .node_attrs
+                .into_iter()
+                .map(|(idx, attrs)| (idx + offset, attrs)),
+        );
+
+        let mut edges = self.edges;
+        for (source, other_edges) in other.edges {
+            let relabeled = other_edges.into_iter().map(|mut e| {
+                e.source += offset;
+                e.target += offset;
+                e
+            });
+            edges.entry(source + offset).or_default().extend(relabeled);
+        }
+
+        Ok(TemporalGraph {
+            node_count: offset + other.node_count,
+            edges,
+            node_attrs,
+            node_id_map,
+        })
+    }
+}
+
+// escapes the XML special characters in text and attribute values
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Compares node ids such that embedded numbers are ordered by value,
+/// e.g. `v2` before `v10`. Ties (like `v01` and `v1`) fall back to plain string order.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    // splits off the leading run of digits or non-digits
+    fn chunk(s: &str) -> (&str, &str) {
+        let digits = s.starts_with(|c: char| c.is_ascii_digit());
+        let end = s
+            .find(|c: char| c.is_ascii_digit() != digits)
+            .unwrap_or(s.len());
+        s.split_at(end)
+    }
+
+    let (mut x, mut y) = (a, b);
+    while !x.is_empty() && !y.is_empty() {
+        let ((cx, rx), (cy, ry)) = (chunk(x), chunk(y));
+        let both_numbers = cx.starts_with(|c: char| c.is_ascii_digit())
+            && cy.starts_with(|c: char| c.is_ascii_digit());
+        let ord = if both_numbers {
+            let (nx, ny) = (cx.trim_start_matches('0'), cy.trim_start_matches('0'));
+            nx.len().cmp(&ny.len()).then_with(|| nx.cmp(ny))
+        } else {
+            cx.cmp(cy)
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+        (x, y) = (rx, ry);
+    }
+    x.len().cmp(&y.len()).then_with(|| a.cmp(b))
+}
+
+/// How a [`TemporalGraphBuilder`] treats a node id that is declared more than once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateNodePolicy {
+    /// `build` fails, listing the repeated ids
+    #[default]
+    Error,
+    /// The node keeps its index and the attributes of all declarations are merged,
+    /// later declarations overriding earlier ones (e.g. a conflicting owner)
+    Merge,
+}
+
+/// Builds a TemporalGraph from string node ids.
+///
+/// Nodes get indices in the order they are first declared.
+/// Edges may refer to nodes declared later; ids are resolved in `build`.
+/// Declaring a node twice is an error, unless allowed by [`DuplicateNodePolicy::Merge`].
+#[derive(Default)]
+pub struct TemporalGraphBuilder {
+    node_id_map: HashMap<String, Node>,
+    node_attrs: HashMap<Node, HashMap<String, NodeAttr>>,
+    edges: Vec<(String, String, Formula, i64)>,
+    time_var: Option<String>,
+    prune_horizon: Option<usize>,
+    duplicate_nodes: DuplicateNodePolicy,
+    // ids declared more than once, in the order of their first repetition
+    duplicates: Vec<String>,
+}
+
+impl TemporalGraphBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a node with the given owner and optional label.
+    pub fn node(self, id: &str, owner: bool, label: Option<&str>) -> Self {
+        let mut attrs = vec![NodeAttr::Owner(owner)];
+        if let Some(label) = label {
+            attrs.push(NodeAttr::Label(label.to_string()));
+        }
+        self.node_with_attrs(id, &attrs)
+    }
+
+    /// Adds a node with an arbitrary list of attributes.
+    pub fn node_with_attrs(mut self, id: &str, attrs: &[NodeAttr]) -> Self {
+        let next_idx = self.node_id_map.len();
+        let idx = *self.node_id_map.entry(id.to_string()).or_insert(next_idx);
+        if idx != next_idx && !self.duplicates.iter().any(|d| d == id) {
+            self.duplicates.push(id.to_string());
+        }
+
+        let attr_map = self.node_attrs.entry(idx).or_default();
+        for a in attrs {
+            match a {
+                NodeAttr::Owner(_) => {
+                    attr_map.insert("owner".to_string(), a.clone());
+                }
+                NodeAttr::Label(_) => {
+                    attr_map.insert("label".to_string(), a.clone());
+                }
+            }
+        }
+        self
+    }
+
+    /// Adds an edge between two node ids, available whenever `formula` holds.
+    pub fn edge(self, from: &str, to: &str, formula: Formula) -> Self {
+        self.weighted_edge(from, to, formula, 0)
+    }
+
+    /// Adds an edge with a weight (energy cost), see [`Edge::with_weight`].
+    pub fn weighted_edge(mut self, from: &str, to: &str, formula: Formula, weight: i64) -> Self {
+        self.edges
+            .push((from.to_string(), to.to_string(), formula, weight));
+        self
+    }
+
+    /// Requires every edge formula to use `time_var` as its only free variable,
+    /// see [`Edge::with_time_var`]. Without this, edges are created with [`Edge::new`].
+    pub fn time_var(mut self, time_var: &str) -> Self {
+        self.time_var = Some(time_var.to_string());
+        self
+    }
+
+    /// Sets how nodes declared more than once are treated, see [`DuplicateNodePolicy`].
+    pub fn duplicate_nodes(mut self, policy: DuplicateNodePolicy) -> Self {
+        self.duplicate_nodes = policy;
+        self
+    }
+
+    /// Simplifies edges whose formula is constant over the times `0..=horizon`:
+    /// always-false edges are dropped and always-true edges skip the formula
+    /// when checking availability. Requires a time variable to be set.
+    ///
+    /// The resulting graph is only faithful for times up to `horizon`,
+    /// i.e. for solving with a time bound of at most `horizon + 1`.
+    pub fn prune_trivial_edges(mut self, horizon: usize) -> Self {
+        self.prune_horizon = Some(horizon);
+        self
+    }
+
+    /// Checks that no edge formula has a free variable other than the time variable,
+    /// reporting every offending edge (one per line) rather than only the first.
+    fn check_free_variables(&self, time_var: &str) -> Result<(), String> {
+        let mut violations = Vec::new();
+        for (from_id, to_id, formula, _) in &self.edges {
+            let mut stray: Vec<&str> = formula
+                .free_variables()
+                .into_iter()
+                .filter(|v| *v != time_var)
+                .collect();
+            if stray.is_empty() {
+                continue;
+            }
+            stray.sort();
+            violations.push(format!(
+                "edge {} -> {}: formula uses variable(s) {} but the time variable is '{}'",
+                from_id,
+                to_id,
+                stray
+                    .iter()
+                    .map(|v| format!("'{}'", v))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                time_var
+            ));
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations.join("\n"))
+        }
+    }
+
+    /// Resolves node ids and builds the graph.
+    /// Fails if an edge refers to an unknown node id, if a node is declared twice
+    /// (unless duplicates are merged), or (if a time variable is set) an edge
+    /// formula does not fit it.
+    ///
+    /// Edge formulas without free variables are replaced by their value,
+    /// `Formula::True` or `Formula::False` (see [`Formula::fold_constant`]).
+    pub fn build(self) -> Result<TemporalGraph, String> {
+        if self.duplicate_nodes == DuplicateNodePolicy::Error && !self.duplicates.is_empty() {
+            return Err(format!(
+                "node(s) declared more than once: {}",
+                self.duplicates.join(", ")
+            ));
+        }
+        if let Some(time_var) = &self.time_var {
+            self.check_free_variables(time_var)?;
+        }
+        let mut edges = Vec::new();
+        // edges with equal formulas share one compiled closure
+        let mut cache = AvailabilityCache::new();
+        for (from_id, to_id, formula, weight) in self.edges {
+            let lookup = |id: &String| {
+                self.node_id_map.get(id).copied().ok_or_else(|| {
+                    format!(
+                        "edge {} -> {} refers to undeclared node {}",
+                        from_id, to_id, id
+                    )
+                })
+            };
+            let from = lookup(&from_id)?;
+            let to = lookup(&to_id)?;
+            let formula = formula.fold_constant();
+
+            let edge = match &self.time_var {
+                Some(time_var) => {
+                    let trivial = self.prune_horizon.map(|h| {
+                        (
+                            formula.is_tautology_over(time_var, h),
+                            formula.is_contradiction_over(time_var, h),
+                        )
+                    });
+                    let edge = Edge::with_time_var_cached(from, to, formula, time_var, &mut cache)
+                        .map_err(|e| format!("edge {} -> {}: {}", from_id, to_id, e))?;
+                    match trivial {
+                        Some((_, true)) => continue,
+                        Some((true, _)) => edge.always_available(),
+                        _ => edge,
+                    }
+                }
+                None => Edge::new_cached(from, to, formula, &mut cache),
+            };
+            edges.push(edge.with_weight(weight));
+        }
+
+        Ok(TemporalGraph::new(
+            self.node_id_map.len(),
+            self.node_id_map,
+            self.node_attrs,
+            edges,
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -216,4 +1195,830 @@ mod tests {
         let successors: Vec<_> = graph.successors_at(1, 5).collect();
         assert_eq!(successors, vec![1]);
     }
+
+    #[test]
+    fn test_two_state_successors_mask_at() {
+        let graph = create_two_state_graph();
+        assert_eq!(graph.successors_mask_at(0, 4), [false, false]);
+        assert_eq!(graph.successors_mask_at(0, 5), [false, true]);
+        for time in 0..8 {
+            for node in graph.nodes() {
+                let mask = graph.successors_mask_at(node, time);
+                let set: Vec<Node> = graph.nodes().filter(|&n| mask[n]).collect();
+                assert_eq!(set, graph.successors_at(node, time).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn test_two_state_timed_edges() {
+        let graph = create_two_state_graph();
+        let triples: Vec<_> = graph.timed_edges(6).collect();
+        // The self-loop of state 1 at all 7 times, and 0 -> 1 at times 5 and 6
+        assert_eq!(triples.len(), 9);
+        assert_eq!(&triples[..2], [(0, 1, 5), (0, 1, 6)]);
+        assert!(
+            triples[2..]
+                .iter()
+                .all(|&(from, to, _)| (from, to) == (1, 1))
+        );
+        assert_eq!(graph.timed_edges(4).count(), 5);
+    }
+
+    #[test]
+    fn test_two_state_constrained_edges() {
+        use crate::formulae::Expr;
+
+        let graph = create_two_state_graph();
+        let constrained: Vec<_> = graph.constrained_edges().collect();
+        let x_ge_5 = Formula::Ge(
+            Box::new(Expr::Var("x".to_string())),
+            Box::new(Expr::Const(5)),
+        );
+        assert_eq!(constrained, [(0, 1, &x_ge_5)]);
+    }
+
+    #[test]
+    fn test_two_state_availability_trace() {
+        let graph = create_two_state_graph();
+        assert_eq!(graph.availability_trace(0, 1, 8), [5, 6, 7, 8]);
+        assert_eq!(graph.availability_trace(1, 1, 3), [0, 1, 2, 3]);
+        assert_eq!(graph.availability_trace(0, 1, 4), []);
+        assert_eq!(graph.availability_trace(1, 0, 8), []);
+    }
+
+    #[test]
+    fn test_two_state_adjacency() {
+        let graph = create_two_state_graph();
+        //  At time 4 only the self-loop of state 1 is available
+        assert_eq!(
+            graph.adjacency_at(4),
+            vec![vec![false, false], vec![false, true]]
+        );
+        //  At time 5 state 0 can also reach state 1
+        assert_eq!(
+            graph.adjacency_at(5),
+            vec![vec![false, true], vec![false, true]]
+        );
+    }
+
+    #[test]
+    fn test_two_state_edges_in_window() {
+        let graph = create_two_state_graph();
+        //  In the window 3..=6, state 0 can reach state 1 only at times 5 and 6
+        let times: Vec<_> = graph
+            .edges_from_in_window(0, 3, 6)
+            .map(|(e, t)| (*e.target(), t))
+            .collect();
+        assert_eq!(times, vec![(1, 5), (1, 6)]);
+
+        //  The self-loop of state 1 is available at every time of the window
+        let times: Vec<_> = graph
+            .edges_from_in_window(1, 3, 6)
+            .map(|(_, t)| t)
+            .collect();
+        assert_eq!(times, vec![3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_availability_period() {
+        use crate::formulae::Expr;
+
+        // the edge 0 -> 1 is constrained by x >= 5, which is not periodic
+        let graph = create_two_state_graph();
+        assert_eq!(graph.availability_period(), None);
+
+        let even = Formula::Eq(
+            Box::new(Expr::Mod(Box::new(Expr::Var("t".to_string())), 2)),
+            Box::new(Expr::Const(0)),
+        );
+        let every_third = Formula::Eq(
+            Box::new(Expr::Mod(Box::new(Expr::Var("t".to_string())), 3)),
+            Box::new(Expr::Const(1)),
+        );
+        let edges = vec![
+            Edge::new(0, 1, even),
+            Edge::new(1, 0, every_third),
+            Edge::new_simple(1, 1),
+        ];
+        let mut node_id_map = HashMap::new();
+        node_id_map.insert("s0".to_string(), 0);
+        node_id_map.insert("s1".to_string(), 1);
+        let graph = TemporalGraph::new(2, node_id_map, HashMap::new(), edges);
+        assert_eq!(graph.availability_period(), Some(6));
+    }
+
+    #[test]
+    fn test_builder_matches_fixture() {
+        use crate::formulae::Expr;
+
+        let built = TemporalGraphBuilder::new()
+            .node("s0", false, Some("s0"))
+            .node("s1", false, Some("s1"))
+            .edge("s1", "s1", Formula::True)
+            .edge(
+                "s0",
+                "s1",
+                Formula::Ge(
+                    Box::new(Expr::Var("x".to_string())),
+                    Box::new(Expr::Const(5)),
+                ),
+            )
+            .build()
+            .expect("build failed");
+        let fixture = create_two_state_graph();
+
+        assert_eq!(built.node_count, fixture.node_count);
+        assert_eq!(built.node_id_map, fixture.node_id_map);
+        assert_eq!(built.node_attrs, fixture.node_attrs);
+        assert_eq!(built.node_ownership(), fixture.node_ownership());
+        for node in fixture.nodes() {
+            assert_eq!(
+                format!("{:?}", built.edges_from(node).collect::<Vec<_>>()),
+                format!("{:?}", fixture.edges_from(node).collect::<Vec<_>>())
+            );
+            for time in 0..8 {
+                assert_eq!(
+                    built.successors_at(node, time).collect::<Vec<_>>(),
+                    fixture.successors_at(node, time).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reachable_from_ids() {
+        use crate::formulae::Expr;
+
+        let graph = TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", true, None)
+            .edge("s0", "s0", Formula::True)
+            .edge("s1", "s1", Formula::True)
+            .edge(
+                "s0",
+                "s1",
+                Formula::Ge(
+                    Box::new(Expr::Var("t".to_string())),
+                    Box::new(Expr::Const(5)),
+                ),
+            )
+            .build()
+            .expect("build failed");
+        let ids = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<HashSet<_>>();
+
+        let target = ids(&["s1"]);
+        assert_eq!(
+            graph.reachable_from_ids(6, true, &target),
+            ids(&["s0", "s1"])
+        );
+        assert_eq!(graph.reachable_from_ids(4, true, &target), ids(&["s1"]));
+        assert_eq!(graph.reachable_from_ids(6, true, &ids(&["s2"])), ids(&[]));
+    }
+
+    #[test]
+    fn test_builder_duplicate_nodes() {
+        let builder = || {
+            TemporalGraphBuilder::new()
+                .node("s0", true, None)
+                .node("s1", true, Some("one"))
+                .node_with_attrs("s0", &[NodeAttr::Label("zero".to_string())])
+                .node("s1", false, None)
+                .node("s1", false, None)
+                .edge("s0", "s1", Formula::True)
+        };
+
+        let err = builder().build().unwrap_err();
+        assert_eq!(err, "node(s) declared more than once: s0, s1");
+
+        let graph = builder()
+            .duplicate_nodes(DuplicateNodePolicy::Merge)
+            .build()
+            .expect("build failed");
+        assert_eq!(graph.node_count, 2);
+        assert_eq!(graph.node_ownership(), [true, false]);
+        assert_eq!(graph.label(0), Some("zero"));
+        assert_eq!(graph.label(1), Some("one"));
+    }
+
+    #[test]
+    fn test_builder_unknown_node() {
+        let result = TemporalGraphBuilder::new()
+            .node("s0", false, None)
+            .edge("s0", "s1", Formula::True)
+            .build();
+        let err = result.expect_err("build should fail");
+        assert!(err.contains("s1"), "error: {}", err);
+    }
+
+    #[test]
+    fn test_builder_time_var() {
+        use crate::formulae::Expr;
+
+        let build = |time_var| {
+            TemporalGraphBuilder::new()
+                .node("s0", false, None)
+                .edge(
+                    "s0",
+                    "s0",
+                    Formula::Ge(
+                        Box::new(Expr::Var("x".to_string())),
+                        Box::new(Expr::Const(5)),
+                    ),
+                )
+                .time_var(time_var)
+                .build()
+        };
+        assert!(build("x").is_ok());
+        assert!(build("t").is_err());
+    }
+
+    #[test]
+    fn test_builder_stray_variables() {
+        use crate::formulae::Expr;
+
+        let ge = |var: &str| {
+            Formula::Ge(
+                Box::new(Expr::Var(var.to_string())),
+                Box::new(Expr::Const(5)),
+            )
+        };
+        let err = TemporalGraphBuilder::new()
+            .node("s0", false, None)
+            .node("s1", false, None)
+            .edge("s0", "s1", ge("s"))
+            .edge("s1", "s1", ge("t"))
+            .edge("s1", "s0", ge("u"))
+            .time_var("t")
+            .build()
+            .expect_err("stray variables should be rejected");
+        assert_eq!(
+            err,
+            "edge s0 -> s1: formula uses variable(s) 's' but the time variable is 't'\n\
+             edge s1 -> s0: formula uses variable(s) 'u' but the time variable is 't'"
+        );
+    }
+
+    #[test]
+    fn test_builder_prune_trivial_edges() {
+        use crate::formulae::Expr;
+
+        let t = || Box::new(Expr::Var("t".to_string()));
+        let five = || Box::new(Expr::Const(5));
+        let build = |prune: bool| {
+            let builder = TemporalGraphBuilder::new()
+                .node("s0", false, None)
+                .node("s1", false, None)
+                .time_var("t")
+                // (or (>= t 5) (< t 5))
+                .edge(
+                    "s0",
+                    "s0",
+                    Formula::Or(vec![Formula::Ge(t(), five()), Formula::Lt(t(), five())]),
+                )
+                // (and (>= t 5) (< t 5))
+                .edge(
+                    "s0",
+                    "s1",
+                    Formula::And(vec![Formula::Ge(t(), five()), Formula::Lt(t(), five())]),
+                )
+                .edge("s1", "s0", Formula::Ge(t(), five()));
+            match prune {
+                true => builder.prune_trivial_edges(10),
+                false => builder,
+            }
+            .build()
+            .unwrap()
+        };
+
+        let pruned = build(true);
+        assert_eq!(build(false).edges().count(), 3);
+        assert_eq!(pruned.edges().count(), 2);
+        // the dropped edge was never available anyway
+        for time in 0..=10 {
+            for node in pruned.nodes() {
+                assert_eq!(
+                    pruned.successors_at(node, time).collect::<Vec<_>>(),
+                    build(false).successors_at(node, time).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_builder_folds_constant_formulas() {
+        let c = |n: i64| Box::new(crate::formulae::Expr::Const(n));
+        let build = |prune: bool| {
+            let builder = TemporalGraphBuilder::new()
+                .node("s0", false, None)
+                .node("s1", false, None)
+                .time_var("t")
+                .edge("s0", "s1", Formula::Ge(c(5), c(3)))
+                .edge("s1", "s0", Formula::Ge(c(3), c(5)));
+            match prune {
+                true => builder.prune_trivial_edges(10),
+                false => builder,
+            }
+            .build()
+            .unwrap()
+        };
+
+        let graph = build(false);
+        let formula = |from: Node| graph.edges_from(from).next().unwrap().formula();
+        assert_eq!(formula(0), &Formula::True);
+        assert_eq!(formula(1), &Formula::False);
+        // the always-false edge is dropped when pruning
+        let pruned = build(true);
+        assert_eq!(pruned.edges().count(), 1);
+        assert_eq!(pruned.edges().next().unwrap().formula(), &Formula::True);
+    }
+
+    #[test]
+    fn test_edge_count_and_out_degree() {
+        let graph = crate::parser::TemporalGraphParser::new()
+            .parse(include_str!("../tests/fixtures/two_state.tg"))
+            .unwrap();
+        let s0 = graph.node_id_map["s0"];
+        let s1 = graph.node_id_map["s1"];
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.out_degree(s0), 2);
+        assert_eq!(graph.out_degree(s1), 1);
+        // not filtered by time: s0 -> s1 is unavailable at time 0
+        assert_eq!(graph.successors_at(s0, 0).count(), 1);
+    }
+
+    #[test]
+    fn test_nodes_of() {
+        let graph = crate::parser::TemporalGraphParser::new()
+            .parse(include_str!("../tests/fixtures/two_state.tg"))
+            .unwrap();
+        // `owner[0]` in the fixture is `NodeAttr::Owner(true)`
+        let mut player0: Vec<Node> = graph.nodes_of(true).collect();
+        player0.sort();
+        assert_eq!(
+            player0,
+            vec![graph.node_id_map["s0"], graph.node_id_map["s1"]]
+        );
+        assert_eq!(graph.nodes_of(false).count(), 0);
+    }
+
+    #[test]
+    fn test_clone() {
+        let graph = create_two_state_graph();
+        let clone = graph.clone();
+        assert_eq!(clone.node_count, graph.node_count);
+        assert_eq!(clone.node_id_map, graph.node_id_map);
+        for n in graph.nodes() {
+            for time in 0..10 {
+                assert_eq!(
+                    clone.successors_at(n, time).collect::<Vec<_>>(),
+                    graph.successors_at(n, time).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_builder_shares_compiled_formulas() {
+        use crate::formulae::Expr;
+
+        let late = || {
+            Formula::Ge(
+                Box::new(Expr::Var("t".to_string())),
+                Box::new(Expr::Const(3)),
+            )
+        };
+        let even = || {
+            Formula::Eq(
+                Box::new(Expr::Mod(Box::new(Expr::Var("t".to_string())), 2)),
+                Box::new(Expr::Const(0)),
+            )
+        };
+        let mut builder = TemporalGraphBuilder::new().time_var("t");
+        for n in 0..100 {
+            builder = builder.node(&format!("v{}", n), true, None);
+        }
+        for n in 0..100 {
+            let (from, to) = (format!("v{}", n), format!("v{}", (n + 1) % 100));
+            builder = builder.edge(&from, &to, late()).edge(&from, &from, even());
+        }
+        let graph = builder.build().expect("build failed");
+
+        for n in graph.nodes() {
+            for time in 0..8 {
+                let mut expected = Vec::new();
+                if time % 2 == 0 {
+                    expected.push(n);
+                }
+                if time >= 3 {
+                    expected.push((n + 1) % 100);
+                }
+                let mut successors: Vec<_> = graph.successors_at(n, time).collect();
+                successors.sort();
+                expected.sort();
+                assert_eq!(successors, expected);
+            }
+        }
+
+        // one closure per distinct formula
+        let shared = |formula: &Formula| {
+            let mut closures = graph.edges().filter(|e| e.formula() == formula);
+            let first = closures.next().unwrap();
+            closures.all(|e| Arc::ptr_eq(&e.available_at, &first.available_at))
+        };
+        assert!(shared(&late()));
+        assert!(shared(&even()));
+    }
+
+    #[test]
+    fn test_all_nodes_selected() {
+        let graph = create_two_state_graph();
+        let all = graph.all_nodes_selected();
+        assert_eq!(all, vec![true, true]);
+        assert_eq!(graph.sorted_ids_from_nodes_vec(&all), ["s0", "s1"]);
+    }
+
+    #[test]
+    fn test_dead_nodes() {
+        use crate::formulae::Expr;
+
+        let graph = TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", true, None)
+            .node("isolated", true, None)
+            .node("late", true, None)
+            .edge("s0", "s0", Formula::True)
+            .edge("s0", "s1", Formula::True)
+            .edge("s1", "s0", Formula::True)
+            .edge("s1", "late", Formula::True)
+            .edge(
+                "late",
+                "s0",
+                Formula::Ge(
+                    Box::new(Expr::Var("t".to_string())),
+                    Box::new(Expr::Const(5)),
+                ),
+            )
+            .time_var("t")
+            .build()
+            .unwrap();
+        let isolated = graph.node_id_map["isolated"];
+        let late = graph.node_id_map["late"];
+
+        assert_eq!(graph.predecessors()[late], vec![graph.node_id_map["s1"]]);
+        assert_eq!(
+            graph.dead_nodes(4),
+            vec![isolated.min(late), isolated.max(late)]
+        );
+        // the edge out of `late` is available from time 5
+        assert_eq!(graph.dead_nodes(5), vec![isolated]);
+    }
+
+    #[test]
+    fn test_nodes_matching_label() {
+        let graph = TemporalGraphBuilder::new()
+            .node("s0", true, Some("goal_a"))
+            .node("s1", true, Some("start"))
+            .node("s2", true, None)
+            .node("s3", false, Some("goal_b"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            graph.nodes_matching_label(|l| l.starts_with("goal_")),
+            vec![true, false, false, true]
+        );
+        // unlabeled nodes never match
+        assert_eq!(
+            graph.nodes_matching_label(|_| true),
+            vec![true, true, false, true]
+        );
+    }
+
+    #[test]
+    fn test_nodes_matching_index() {
+        let graph = TemporalGraphBuilder::new()
+            .node("a", true, None)
+            .node("b", true, None)
+            .node("c", false, None)
+            .node("d", false, None)
+            .node("e", true, None)
+            .build()
+            .unwrap();
+        assert_eq!(
+            graph.nodes_matching_index(|n| n % 2 == 0),
+            vec![true, false, true, false, true]
+        );
+        assert_eq!(
+            graph.nodes_matching_index(|n| n < 2),
+            vec![true, true, false, false, false]
+        );
+    }
+
+    #[test]
+    fn test_interval_edges() {
+        use crate::formulae::Expr;
+
+        let t = || Box::new(Expr::Var("t".to_string()));
+        let c = |n| Box::new(Expr::Const(n));
+        for formula in [
+            Formula::And(vec![Formula::Ge(t(), c(3)), Formula::Le(t(), c(7))]),
+            Formula::And(vec![Formula::Gt(t(), c(3)), Formula::Lt(c(3), t())]),
+            Formula::Eq(t(), c(5)),
+            Formula::And(vec![Formula::Ge(t(), c(7)), Formula::Le(t(), c(3))]),
+            Formula::True,
+        ] {
+            assert!(formula.as_interval("t").is_some());
+            let closure = formula.clone().as_closure().unwrap();
+            let edge = Edge::with_time_var(0, 0, formula, "t").unwrap();
+            assert!((0..20).all(|time| edge.is_available(time) == closure(time)));
+        }
+    }
+
+    #[test]
+    fn test_ap_union_edges() {
+        use crate::formulae::Expr;
+
+        let residue = |m, r| {
+            Formula::Eq(
+                Box::new(Expr::Mod(Box::new(Expr::Var("t".to_string())), m)),
+                Box::new(Expr::Const(r)),
+            )
+        };
+        for formula in [
+            Formula::Or(vec![residue(3, 0), residue(5, 1)]),
+            residue(4, 2),
+            Formula::Or(vec![residue(2, 2)]),
+        ] {
+            assert!(formula.as_ap_union("t").is_some());
+            let closure = formula.clone().as_closure().unwrap();
+            let edge = Edge::with_time_var(0, 0, formula, "t").unwrap();
+            assert!((0..60).all(|time| edge.is_available(time) == closure(time)));
+        }
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        let graph = TemporalGraphBuilder::new().build().unwrap();
+        assert_eq!(graph.nodes().count(), 0);
+        assert_eq!(
+            graph.stats(),
+            GraphStats {
+                node_count: 0,
+                edge_count: 0,
+                player0_nodes: 0,
+                player1_nodes: 0,
+                max_out_degree: 0,
+                constrained_edges: 0,
+            }
+        );
+        assert_eq!(graph.to_tg(), "");
+        assert!(
+            graph
+                .to_graphml()
+                .contains("<graph id=\"G\" edgedefault=\"directed\">")
+        );
+        assert_eq!(graph.dead_nodes(3), Vec::<Node>::new());
+    }
+
+    #[test]
+    fn test_max_meaningful_time() {
+        use crate::formulae::Expr;
+
+        let t = || Box::new(Expr::Var("t".to_string()));
+        let c = |n| Box::new(Expr::Const(n));
+        let graph = |formulas: Vec<Formula>| {
+            let mut builder = TemporalGraphBuilder::new()
+                .time_var("t")
+                .node("s0", true, None);
+            for formula in formulas {
+                builder = builder.edge("s0", "s0", formula);
+            }
+            builder.build().unwrap().max_meaningful_time()
+        };
+
+        assert_eq!(graph(vec![]), Some(0));
+        assert_eq!(graph(vec![Formula::True]), Some(0));
+        // available at 2..=4, from 6 on, and exactly at 3
+        let bounded = vec![
+            Formula::And(vec![Formula::Ge(t(), c(2)), Formula::Le(t(), c(4))]),
+            Formula::Gt(t(), c(5)),
+            Formula::Eq(t(), c(3)),
+        ];
+        assert_eq!(graph(bounded.clone()), Some(6));
+        let mut periodic = bounded;
+        periodic.push(Formula::Eq(Box::new(Expr::Mod(t(), 2)), c(0)));
+        assert_eq!(graph(periodic), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(create_two_state_graph().validate(), Ok(()));
+        assert_eq!(
+            TemporalGraphBuilder::new().build().unwrap().validate(),
+            Ok(())
+        );
+
+        let node_id_map = HashMap::from([
+            ("a".to_string(), 0),
+            ("b".to_string(), 0),
+            ("c".to_string(), 5),
+        ]);
+        let node_attrs = HashMap::from([(7, HashMap::new())]);
+        let edges = vec![Edge::new_simple(0, 1), Edge::new_simple(3, 0)];
+        let graph = TemporalGraph::new(2, node_id_map, node_attrs, edges);
+        assert_eq!(
+            graph.validate().unwrap_err().lines().collect::<Vec<_>>(),
+            [
+                "node c has index 5, but the graph has 2 nodes",
+                "nodes a, b share index 0",
+                "node index 1 has no id",
+                "attributes given for node index 7, but the graph has 2 nodes",
+                "edge 3 -> 0 refers to a node index outside the graph's 2 nodes",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = create_two_state_graph().stats();
+        assert_eq!(
+            stats,
+            GraphStats {
+                node_count: 2,
+                edge_count: 2,
+                player0_nodes: 0,
+                player1_nodes: 2,
+                max_out_degree: 1,
+                constrained_edges: 1,
+            }
+        );
+        assert_eq!(
+            TemporalGraphBuilder::new()
+                .build()
+                .unwrap()
+                .stats()
+                .max_out_degree,
+            0
+        );
+    }
+
+    #[test]
+    fn test_to_graphml() {
+        use crate::formulae::Expr;
+        use quick_xml::{Reader, events::Event};
+
+        let graph = TemporalGraphBuilder::new()
+            .node("s0", true, Some("start & \"go\""))
+            .node("s1", false, None)
+            .edge(
+                "s0",
+                "s1",
+                Formula::Lt(
+                    Box::new(Expr::Var("t".to_string())),
+                    Box::new(Expr::Const(5)),
+                ),
+            )
+            .build()
+            .unwrap();
+        let xml = graph.to_graphml();
+
+        assert!(xml.contains("<node id=\"s0\">"));
+        assert!(xml.contains("<data key=\"owner\">0</data>"));
+        assert!(xml.contains("<data key=\"label\">start &amp; &quot;go&quot;</data>"));
+        assert!(xml.contains("<node id=\"s1\">"));
+        assert!(xml.contains("<data key=\"owner\">1</data>"));
+        assert!(xml.contains("<edge source=\"s0\" target=\"s1\">"));
+        assert!(xml.contains("<data key=\"formula\">(&lt; t 5)</data>"));
+
+        // well-formed, with the expected elements
+        let mut reader = Reader::from_str(&xml);
+        reader.config_mut().check_end_names = true;
+        let mut elements = HashMap::<String, usize>::new();
+        loop {
+            match reader.read_event().expect("invalid XML") {
+                Event::Eof => break,
+                Event::Start(e) | Event::Empty(e) => {
+                    let name = e.name().0.to_string();
+                    *elements.entry(name).or_default() += 1;
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(elements["key"], 3);
+        assert_eq!(elements["node"], 2);
+        assert_eq!(elements["edge"], 1);
+        assert_eq!(elements["data"], 4);
+    }
+
+    #[test]
+    fn test_merge() {
+        let left = create_two_state_graph();
+        let right = TemporalGraphBuilder::new()
+            .node("s0", true, None)
+            .node("s1", false, None)
+            .edge("s1", "s0", Formula::True)
+            .build()
+            .unwrap();
+
+        assert!(
+            create_two_state_graph()
+                .merge(create_two_state_graph(), None)
+                .is_err()
+        );
+
+        let merged = left.merge(right, Some("r_")).unwrap();
+        assert_eq!(merged.node_count, 4);
+        assert_eq!(merged.node_id_map["s0"], 0);
+        assert_eq!(merged.node_id_map["s1"], 1);
+        assert_eq!(merged.node_id_map["r_s0"], 2);
+        assert_eq!(merged.node_id_map["r_s1"], 3);
+        assert_eq!(merged.node_ownership(), vec![false, false, true, false]);
+
+        assert_eq!(merged.edges().count(), 3);
+        assert_eq!(merged.successors_at(0, 5).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(merged.successors_at(1, 0).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(merged.successors_at(2, 0).count(), 0);
+        assert_eq!(merged.successors_at(3, 0).collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_subgraph() {
+        let graph = create_two_state_graph();
+        let sub = graph.subgraph(&[false, true]);
+
+        assert_eq!(sub.node_count, 1);
+        assert_eq!(sub.node_id_map, HashMap::from([("s1".to_string(), 0)]));
+        assert_eq!(
+            sub.node_attrs[&0]["label"],
+            NodeAttr::Label("s1".to_string())
+        );
+        // only the self-loop of s1 remains
+        assert_eq!(sub.edges().count(), 1);
+        assert_eq!(sub.successors_at(0, 5).collect::<Vec<_>>(), vec![0]);
+
+        // the kept edges share their closures with the original graph
+        let original = graph.edges_from(1).next().unwrap();
+        let kept = sub.edges_from(0).next().unwrap();
+        assert!(Arc::ptr_eq(&original.available_at, &kept.available_at));
+    }
+
+    #[test]
+    fn test_relabel() {
+        let mut graph = create_two_state_graph();
+        let mapping = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        // unknown ids and collisions leave the graph unchanged
+        assert!(graph.relabel(&mapping(&[("s7", "a")])).is_err());
+        assert!(graph.relabel(&mapping(&[("s0", "s1")])).is_err());
+        assert_eq!(graph.sorted_ids_from_nodes_vec(&[true, true]), ["s0", "s1"]);
+
+        graph
+            .relabel(&mapping(&[("s0", "a"), ("s1", "b")]))
+            .unwrap();
+        assert_eq!(graph.sorted_ids_from_nodes_vec(&[true, true]), ["a", "b"]);
+        assert_eq!(
+            graph.ids_from_nodes_vec(&[false, true]),
+            HashSet::from(["b".to_string()])
+        );
+
+        // swapping two ids is not a collision
+        graph.relabel(&mapping(&[("a", "b"), ("b", "a")])).unwrap();
+        assert_eq!(graph.node_id_map["a"], 1);
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut ids = vec!["v10", "v2", "w", "v1", "v01", "v", "u3x2", "u3x10"];
+        ids.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(ids, ["u3x2", "u3x10", "v", "v01", "v1", "v2", "v10", "w"]);
+    }
+
+    #[test]
+    fn test_nodes_selected_from_ids_checked() {
+        let graph = create_two_state_graph();
+        let ids = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<HashSet<_>>();
+
+        assert_eq!(
+            graph.nodes_selected_from_ids_checked(&ids(&["s1"])),
+            Ok(vec![false, true])
+        );
+        assert_eq!(
+            graph.nodes_selected_from_ids_checked(&ids(&["s0", "s1"])),
+            Ok(vec![true, true])
+        );
+        assert_eq!(
+            graph.nodes_selected_from_ids_checked(&ids(&["s1", "sl", "s2"])),
+            Err(vec!["s2".to_string(), "sl".to_string()])
+        );
+        // the lenient variant ignores unknown ids
+        assert_eq!(
+            graph.nodes_selected_from_ids(&ids(&["s1", "sl", "s2"])),
+            vec![false, true]
+        );
+    }
 }
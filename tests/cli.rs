@@ -0,0 +1,1098 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use flate2::{Compression, write::GzEncoder};
+
+fn ontime() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_ontime"))
+}
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn test_json_output() {
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--target-set",
+            "s1",
+            "--time-to-reach",
+            "6",
+            "--json",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("output is not valid JSON");
+    assert_eq!(json["file"], fixture("two_state.tg"));
+    assert_eq!(json["k"], 6);
+    assert_eq!(json["target"], serde_json::json!(["s1"]));
+    assert_eq!(json["winning"], serde_json::json!(["s0", "s1"]));
+    assert!(json["time"].as_f64().is_some());
+    assert_eq!(json["stats"]["iterations"], 6);
+    assert_eq!(json["stats"]["flips"], 1);
+    assert_eq!(json["stats"]["early_fixpoint"], false);
+}
+
+#[test]
+fn test_start_winning() {
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--target-set",
+            "s1",
+            "--time-to-reach",
+            "6",
+            "--start",
+            "s0",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "WIN");
+}
+
+#[test]
+fn test_start_losing() {
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--target-set",
+            "s1",
+            "--time-to-reach",
+            "5",
+            "--start",
+            "s0",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "LOSE");
+}
+
+#[test]
+fn test_start_unknown_node() {
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--target-set",
+            "s1",
+            "--start",
+            "nope",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("nope"));
+}
+
+#[test]
+fn test_time_range_matches_individual_runs() {
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--target-set",
+            "s1",
+            "--time-range",
+            "0..=8",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 9);
+
+    for (k, line) in lines.iter().enumerate() {
+        let output = ontime()
+            .args([
+                &fixture("two_state.tg"),
+                "--target-set",
+                "s1",
+                "--time-to-reach",
+                &k.to_string(),
+                "--json",
+            ])
+            .output()
+            .expect("failed to run ontime");
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let winning: Vec<String> = serde_json::from_value(json["winning"].clone()).unwrap();
+        assert_eq!(*line, format!("W_0[k={}] = {:?}", k, winning));
+    }
+}
+
+#[test]
+fn test_time_range_csv() {
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--target-set",
+            "s1",
+            "--time-range",
+            "4..7",
+            "--csv",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    let file = fixture("two_state.tg");
+    let expected: Vec<String> = [(4, "s1"), (5, "s1"), (6, "s0;s1")]
+        .iter()
+        .map(|(k, w)| format!("Ontime Punctual Reachability Solver,{},{},{}", file, k, w))
+        .collect();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .collect::<Vec<_>>(),
+        expected
+    );
+}
+
+#[test]
+fn test_time_range_conflicts_with_timeout() {
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--time-range",
+            "0..=6",
+            "--timeout",
+            "5",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "stderr: {}", stderr);
+}
+
+#[test]
+fn test_time_range_empty() {
+    for range in ["5..=2", "3..3", "0..0", "4..2"] {
+        let output = ontime()
+            .args([&fixture("two_state.tg"), "--time-range", range])
+            .output()
+            .expect("failed to run ontime");
+        assert!(!output.status.success(), "{}", range);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("empty time range"), "stderr: {}", stderr);
+    }
+}
+
+#[test]
+fn test_no_self_loops() {
+    let winning = |extra: &[&str]| {
+        let output = ontime()
+            .args([
+                &fixture("two_state.tg"),
+                "--target-set",
+                "s1",
+                "--time-to-reach",
+                "6",
+            ])
+            .args(extra)
+            .output()
+            .expect("failed to run ontime");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .last()
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(winning(&[]), r#"W_0 = ["s0", "s1"]"#);
+    // state 0 cannot wait for the edge to state 1
+    assert_eq!(winning(&["--no-self-loops"]), "W_0 = []");
+}
+
+#[test]
+fn test_batch() {
+    let dir = fixture("batch");
+    let output = ontime()
+        .args(["--batch", &dir])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows: Vec<Vec<&str>> = stdout.lines().map(|l| l.split(',').collect()).collect();
+    assert_eq!(rows.len(), 3);
+    let expected = [
+        ("a.tg", "solved"),
+        ("b.tg", "solved"),
+        ("broken.tg", "error"),
+    ];
+    for (row, (file, status)) in rows.iter().zip(expected) {
+        assert_eq!(row.len(), 4);
+        assert_eq!(row[0], "Ontime Punctual Reachability Solver");
+        assert_eq!(row[1], format!("{}/{}", dir, file));
+        assert_eq!(row[2], status);
+        assert!(row[3].parse::<f64>().is_ok());
+    }
+}
+
+#[test]
+fn test_batch_jobs() {
+    // a directory with several copies of the batch fixtures
+    let dir = std::env::temp_dir().join(format!("ontime_batch_jobs_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for i in 0..4 {
+        for file in ["a.tg", "b.tg", "broken.tg"] {
+            let name = format!("{}_{}", i, file);
+            std::fs::copy(fixture(&format!("batch/{}", file)), dir.join(name)).unwrap();
+        }
+    }
+    let dir = dir.to_string_lossy().into_owned();
+
+    // the rows without the time column
+    let rows = |jobs: &str| {
+        let output = ontime()
+            .args(["--batch", &dir, "--jobs", jobs])
+            .output()
+            .expect("failed to run ontime");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|l| l.rsplit_once(',').unwrap().0.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let sequential = rows("1");
+    assert_eq!(sequential.len(), 12);
+    assert!(sequential.is_sorted());
+    assert_eq!(
+        sequential[..3],
+        [
+            format!("Ontime Punctual Reachability Solver,{}/0_a.tg,solved", dir),
+            format!("Ontime Punctual Reachability Solver,{}/0_b.tg,solved", dir),
+            format!(
+                "Ontime Punctual Reachability Solver,{}/0_broken.tg,error",
+                dir
+            ),
+        ]
+    );
+    for _ in 0..3 {
+        assert_eq!(rows("4"), sequential);
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_missing_file() {
+    let output = ontime()
+        .arg(fixture("does_not_exist.tg"))
+        .output()
+        .expect("failed to run ontime");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("I/O error"), "stderr: {}", stderr);
+    assert!(!stderr.contains("panicked"), "stderr: {}", stderr);
+}
+
+#[test]
+fn test_bad_target_string() {
+    let output = ontime()
+        .args([&fixture("two_state.tg"), "--target-set", "s0,,s1"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("failed to parse target set"),
+        "stderr: {}",
+        stderr
+    );
+    assert!(!stderr.contains("panicked"), "stderr: {}", stderr);
+}
+
+#[test]
+fn test_csv_error_row() {
+    let output = ontime()
+        .args([&fixture("batch/broken.tg"), "--csv"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let row: Vec<&str> = stdout.trim().split(',').collect();
+    assert_eq!(row[1], fixture("batch/broken.tg"));
+    assert_eq!(row[2], "error");
+}
+
+#[test]
+fn test_unknown_target_warning() {
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--target-set",
+            "s1,s7",
+            "--time-to-reach",
+            "6",
+            "--json",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("unknown target nodes: s7"),
+        "stderr: {}",
+        stderr
+    );
+
+    // the known targets are still solved for
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["target"], serde_json::json!(["s1"]));
+    assert_eq!(json["winning"], serde_json::json!(["s0", "s1"]));
+}
+
+#[test]
+fn test_count_only() {
+    let args = [
+        &fixture("two_state.tg"),
+        "--target-set",
+        "s1",
+        "--time-to-reach",
+        "6",
+        "--count-only",
+    ];
+    let output = ontime().args(args).output().expect("failed to run ontime");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "2 1.000000");
+
+    let output = ontime()
+        .args(args)
+        .arg("--csv")
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let row: Vec<&str> = stdout.trim().split(',').collect();
+    assert_eq!(row[2], "solved");
+    assert_eq!(&row[4..], ["2", "1.000000"]);
+
+    let output = ontime()
+        .args(args)
+        .arg("--json")
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("output is not valid JSON");
+    assert_eq!(json["winning"], serde_json::json!(["s0", "s1"]));
+    assert_eq!(json["count"], 2);
+    assert_eq!(json["ratio"], 1.0);
+}
+
+#[test]
+fn test_stats() {
+    let output = ontime()
+        .args([&fixture("two_state.tg"), "--stats"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .collect::<Vec<_>>(),
+        [
+            "nodes: 2",
+            "edges: 3",
+            "player 0 nodes: 2",
+            "player 1 nodes: 0",
+            "max out-degree: 2",
+            "constrained edges: 1",
+        ]
+    );
+}
+
+#[test]
+fn test_standard_output_is_sorted() {
+    let output = ontime()
+        .args([
+            &fixture("numbered.tg"),
+            "--target-set",
+            "v1,v2",
+            "--time-to-reach",
+            "1",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "W_1 = [\"v1\", \"v2\"]\nW_0 = [\"v1\", \"v2\", \"v10\"]\n"
+    );
+}
+
+#[test]
+fn test_gzip_input() {
+    let plain = std::fs::read(fixture("two_state.tg")).unwrap();
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&plain).unwrap();
+    let gzipped = encoder.finish().unwrap();
+    let gz_path = format!("{}/two_state.tg.gz", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&gz_path, &gzipped).unwrap();
+
+    let args = ["--target-set", "s1", "--time-to-reach", "6"];
+    let expected = ontime()
+        .arg(fixture("two_state.tg"))
+        .args(args)
+        .output()
+        .expect("failed to run ontime");
+    assert!(expected.status.success());
+
+    let from_file = ontime()
+        .arg(&gz_path)
+        .args(args)
+        .output()
+        .expect("failed to run ontime");
+    assert!(from_file.status.success());
+    assert_eq!(from_file.stdout, expected.stdout);
+
+    let mut child = ontime()
+        .args(args)
+        .arg("--gzip")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run ontime");
+    child.stdin.take().unwrap().write_all(&gzipped).unwrap();
+    let from_stdin = child.wait_with_output().unwrap();
+    assert!(from_stdin.status.success());
+    assert_eq!(from_stdin.stdout, expected.stdout);
+}
+
+#[test]
+fn test_targets_file() {
+    let targets = format!("{}/two_state.targets", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&targets, "s1\ns0,s1\n").unwrap();
+
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--time-to-reach",
+            "5",
+            "--targets-file",
+            &targets,
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows: Vec<Vec<&str>> = stdout.lines().map(|l| l.split(',').collect()).collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0][1..], [fixture("two_state.tg").as_str(), "s1", "s1"]);
+    assert_eq!(
+        rows[1][1..],
+        [fixture("two_state.tg").as_str(), "s0;s1", "s0;s1"]
+    );
+}
+
+#[test]
+fn test_targets_file_skips_bad_lines() {
+    let targets = format!("{}/two_state_bad.targets", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&targets, "s0 s1\ns1\n").unwrap();
+
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--time-to-reach",
+            "6",
+            "--targets-file",
+            &targets,
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("skipping line 1"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(stdout.trim().ends_with(",s1,s0;s1"));
+}
+
+#[test]
+fn test_timeout() {
+    // a chain of nodes whose edges are not constant, so the solver cannot stop early
+    let mut graph = String::new();
+    for n in 0..200 {
+        graph.push_str(&format!("node v{}: owner[0]\n", n));
+    }
+    for n in 0..200 {
+        graph.push_str(&format!("edge v{} -> v{}: (>= t 0)\n", n, (n + 1) % 200));
+    }
+    let path = format!("{}/large.tg", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&path, graph).unwrap();
+
+    let args = [
+        &path,
+        "--target-set",
+        "v0",
+        "--time-to-reach",
+        "1000000000",
+        "--timeout",
+        "0.05",
+    ];
+    let output = ontime()
+        .args(args)
+        .arg("--csv")
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(output.status.code(), Some(124));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let row: Vec<&str> = stdout.trim().split(',').collect();
+    assert_eq!(row[1..3], [path.as_str(), "timeout"]);
+
+    let output = ontime()
+        .args(args)
+        .arg("--json")
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(output.status.code(), Some(124));
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("output is not valid JSON");
+    assert_eq!(json["status"], "timeout");
+}
+
+#[test]
+fn test_meta_file() {
+    // time bound 6, target s1 and start s0 all come from meta.meta
+    let output = ontime()
+        .arg(fixture("meta.tg"))
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "WIN");
+    assert!(output.status.success());
+
+    // an explicit start node takes priority over the .meta file
+    let output = ontime()
+        .args([&fixture("meta.tg"), "--start", "s1"])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "WIN");
+}
+
+#[test]
+fn test_pretty_output() {
+    let output = ontime()
+        .args([
+            &fixture("labeled.tg"),
+            "--target-set",
+            "s1",
+            "--time-to-reach",
+            "6",
+            "--pretty",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines[..7],
+        [
+            "Nodes: 2",
+            "Time bound: 6",
+            "Target nodes:",
+            "  Goal",
+            "Winning nodes:",
+            "  Start",
+            "  Goal",
+        ]
+    );
+    assert!(lines[7].starts_with("Solve time: "), "{}", lines[7]);
+
+    let output = ontime()
+        .args([
+            &fixture("labeled.tg"),
+            "--target-set",
+            "s1",
+            "--time-to-reach",
+            "5",
+            "--start",
+            "s0",
+            "--pretty",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "Node Start is losing at horizon 5"
+    );
+}
+
+#[test]
+fn test_certificate() {
+    let certificate = |k: &str| {
+        let output = ontime()
+            .args([
+                &fixture("two_state.tg"),
+                "--target-set",
+                "s1",
+                "--time-to-reach",
+                k,
+                "--csv",
+                "--certificate",
+            ])
+            .output()
+            .expect("failed to run ontime");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        assert_eq!(stdout.lines().count(), 1);
+        let row: Vec<&str> = stdout.trim().split(',').collect();
+        assert_eq!(row.len(), 5);
+        assert_eq!(row[2], "solved");
+        row[4].to_string()
+    };
+    assert_eq!(certificate("6"), "s0;s1");
+    assert_eq!(certificate("5"), "s1");
+}
+
+#[test]
+fn test_target_set_all() {
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--target-set",
+            "all",
+            "--time-to-reach",
+            "3",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "W_3 = [\"s0\", \"s1\"]\nW_0 = [\"s0\", \"s1\"]\n"
+    );
+}
+
+#[test]
+fn test_empty_target_set() {
+    let output = ontime()
+        .args([&fixture("two_state.tg"), "--target-set", "nosuch"])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Empty target set"), "{}", stderr);
+
+    // with --csv the failure is reported as a row
+    let output = ontime()
+        .args([&fixture("two_state.tg"), "--target-set", "nosuch", "--csv"])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(",error,"), "{}", stdout);
+}
+
+#[test]
+fn test_lint() {
+    let output = ontime()
+        .args([&fixture("two_state.tg"), "--lint"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "No dead nodes\n");
+
+    let mut child = ontime()
+        .args(["-", "--lint", "--time-to-reach", "3"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run ontime");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"node s0\nnode s1\nnode isolated\nedge s0 -> s1\nedge s1 -> s0\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Dead node: isolated\n"
+    );
+}
+
+#[test]
+fn test_target_label_prefix() {
+    let output = ontime()
+        .args([
+            &fixture("labeled.tg"),
+            "--target-label-prefix",
+            "Go",
+            "--time-to-reach",
+            "6",
+            "--json",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("output is not valid JSON");
+    assert_eq!(json["target"], serde_json::json!(["s1"]));
+    assert_eq!(json["winning"], serde_json::json!(["s0", "s1"]));
+
+    let output = ontime()
+        .args([&fixture("labeled.tg"), "--target-label-prefix", "goal"])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_target_expr() {
+    // the nodes v1, v2 and v10 have the indices 0, 1 and 2
+    let winning = |expr: &str| {
+        let output = ontime()
+            .args([
+                &fixture("numbered.tg"),
+                "--target-expr",
+                expr,
+                "--time-to-reach",
+                "1",
+                "--json",
+            ])
+            .output()
+            .expect("failed to run ontime");
+        assert!(output.status.success());
+        let json: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("output is not valid JSON");
+        (json["target"].clone(), json["winning"].clone())
+    };
+    assert_eq!(
+        winning("(= (mod n 2) 0)"),
+        (
+            serde_json::json!(["v1", "v10"]),
+            serde_json::json!(["v1", "v10"])
+        )
+    );
+    assert_eq!(
+        winning("(= n 1)"),
+        (serde_json::json!(["v2"]), serde_json::json!(["v2", "v10"]))
+    );
+
+    // more than one variable
+    let output = ontime()
+        .args([&fixture("numbered.tg"), "--target-expr", "(< n m)"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("failed to parse target set"),
+        "stderr: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_time_bound_zero() {
+    let output = ontime()
+        .args([
+            &fixture("two_state.tg"),
+            "--target-set",
+            "s1",
+            "--time-to-reach",
+            "0",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "W_0 = [\"s1\"]\n");
+}
+
+#[test]
+fn test_time_to_reach_override() {
+    // meta.meta sets the time bound to 6, enough for s0 to win
+    let output = ontime()
+        .args([&fixture("meta.tg"), "--time-to-reach", "2"])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "WIN");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--time-to-reach 2 is overridden by the time bound 6"),
+        "stderr: {}",
+        stderr
+    );
+
+    // no warning if the values agree
+    let output = ontime()
+        .args([&fixture("meta.tg"), "--time-to-reach", "6"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.stderr.is_empty());
+
+    let output = ontime()
+        .args([
+            &fixture("meta.tg"),
+            "--time-to-reach",
+            "2",
+            "--force-time-to-reach",
+        ])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "LOSE");
+    assert!(output.stderr.is_empty());
+
+    // on a file, --k is overridden like --time-to-reach, and can be forced too
+    let output = ontime()
+        .args([&fixture("meta.tg"), "--k", "2"])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "WIN");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--k 2 is overridden by the time bound 6"),
+        "stderr: {}",
+        stderr
+    );
+    let output = ontime()
+        .args([&fixture("meta.tg"), "--k", "2", "--force-time-to-reach"])
+        .output()
+        .expect("failed to run ontime");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "LOSE");
+    assert!(output.stderr.is_empty());
+
+    // --force-time-to-reach needs an explicit time bound
+    let output = ontime()
+        .args([&fixture("meta.tg"), "--force-time-to-reach"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_show_constraints() {
+    let output = ontime()
+        .args([&fixture("two_state.tg"), "--show-constraints"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "s0 -> s1: (>= t 5)\n"
+    );
+}
+
+#[test]
+fn test_player() {
+    // both nodes belong to player 0: as player 1, the opponent waits at s0
+    let winning = |args: &[&str]| {
+        let output = ontime()
+            .args([
+                &fixture("two_state.tg"),
+                "--target-set",
+                "s1",
+                "--time-to-reach",
+                "6",
+                "--json",
+            ])
+            .args(args)
+            .output()
+            .expect("failed to run ontime");
+        assert!(output.status.success());
+        let json: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("output is not valid JSON");
+        json["winning"].clone()
+    };
+    assert_eq!(winning(&[]), serde_json::json!(["s0", "s1"]));
+    assert_eq!(winning(&["--player", "0"]), serde_json::json!(["s0", "s1"]));
+    assert_eq!(winning(&["--player", "1"]), serde_json::json!(["s1"]));
+
+    // a player directive in the file
+    let mut child = ontime()
+        .args(["-", "--target-set", "s1", "--time-to-reach", "6"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run ontime");
+    let input = std::fs::read_to_string(fixture("two_state.tg")).unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(format!("// player: 1\n{}", input).as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("W_6 = [\"s1\"]\n"));
+
+    // an invalid player directive is ignored with a warning
+    let mut child = ontime()
+        .args(["-", "--target-set", "s1", "--time-to-reach", "6"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run ontime");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(format!("// player: 3\n{}", input).as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("W_0 = [\"s0\", \"s1\"]\n"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Warning: ignoring player directive: invalid player 3"),
+        "stderr: {}",
+        stderr
+    );
+
+    let output = ontime()
+        .args([&fixture("two_state.tg"), "--player", "2"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_window() {
+    let json = |args: &[&str]| {
+        let output = ontime()
+            .args([&fixture("two_state.tg"), "--target-set", "s1", "--json"])
+            .args(args)
+            .output()
+            .expect("failed to run ontime");
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout)
+            .expect("output is not valid JSON")
+    };
+    // s0 can reach s1 at time 6, but not at time 4
+    let punctual = json(&["--time-to-reach", "4"]);
+    assert_eq!(punctual["winning"], serde_json::json!(["s1"]));
+    let window = json(&["--window", "4:6"]);
+    assert_eq!(window["k"], 6);
+    assert_eq!(window["winning"], serde_json::json!(["s0", "s1"]));
+
+    for bad in ["6:4", "4", "a:6"] {
+        let output = ontime()
+            .args([&fixture("two_state.tg"), "--window", bad])
+            .output()
+            .expect("failed to run ontime");
+        assert!(!output.status.success(), "accepted --window {}", bad);
+    }
+}
+
+#[test]
+fn test_check() {
+    let output = ontime()
+        .args([&fixture("two_state.tg"), "--check"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "OK\n");
+
+    let mut child = ontime()
+        .args(["-", "--check"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run ontime");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"node s0\nedge s0 -> s1\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("undeclared node s1"), "stderr: {}", stderr);
+}
+
+#[test]
+fn test_auto_horizon() {
+    // availability in two_state.tg no longer changes from time 5 on
+    for k in ["4", "6", "1000000"] {
+        let solve = |extra: &[&str]| {
+            let output = ontime()
+                .args([&fixture("two_state.tg"), "--target-set", "s0"])
+                .args(["--time-to-reach", k])
+                .args(extra)
+                .output()
+                .expect("failed to run ontime");
+            assert!(output.status.success());
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        };
+        assert_eq!(solve(&["--auto-horizon"]), solve(&[]), "k = {}", k);
+    }
+}
+
+#[test]
+fn test_k_on_stdin() {
+    let input = std::fs::read_to_string(fixture("two_state.tg")).unwrap();
+    let input = format!("// time_bound: 6\n{}", input);
+    let run = |args: &[&str]| {
+        let mut child = ontime()
+            .args(["-", "--target-set", "s1"])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("failed to run ontime");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        output
+    };
+
+    // the embedded time bound wins over --time-to-reach...
+    let output = run(&["--time-to-reach", "4"]);
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("W_6 = "));
+    assert!(!output.stderr.is_empty());
+
+    // ...but not over --k, silently
+    let output = run(&["--k", "4"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "W_4 = [\"s1\"]\nW_0 = [\"s1\"]\n"
+    );
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_trace_edge() {
+    let output = ontime()
+        .args([&fixture("two_state.tg"), "--trace-edge", "s0", "s1"])
+        .args(["--time-to-reach", "8"])
+        .output()
+        .expect("failed to run ontime");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "s0 -> s1: [5, 6, 7, 8]\n"
+    );
+
+    for (from, to, error) in [
+        ("s1", "s0", "No edge s1 -> s0"),
+        ("s0", "s9", "Unknown node: s9"),
+    ] {
+        let output = ontime()
+            .args([&fixture("two_state.tg"), "--trace-edge", from, to])
+            .output()
+            .expect("failed to run ontime");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains(error));
+    }
+}
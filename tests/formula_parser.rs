@@ -1,8 +1,9 @@
 use ontime::formulae::{Expr, Formula};
+use ontime::parser;
 use ontime::parser::formula::FormulaParser;
 
 fn parse_formula(input: &str) -> Formula {
-    FormulaParser::new().parse(input).expect("parse failed")
+    parser::parse_formula(input).expect("parse failed")
 }
 
 #[test]
@@ -54,3 +55,273 @@ fn test_parse_forall_exists() {
         panic!("Expected Forall");
     }
 }
+
+#[test]
+fn test_parse_negative_literal() {
+    let f = parse_formula("(= x -5)");
+    assert_eq!(
+        f,
+        Formula::Eq(
+            Box::new(Expr::Var("x".to_string())),
+            Box::new(Expr::Const(-5))
+        )
+    );
+    let f = parse_formula("(>= x -3)");
+    assert_eq!(
+        f,
+        Formula::Ge(
+            Box::new(Expr::Var("x".to_string())),
+            Box::new(Expr::Const(-3))
+        )
+    );
+}
+
+#[test]
+fn test_parse_hex_and_binary_literals() {
+    let f = parse_formula("(= x 0xff)");
+    assert_eq!(
+        f,
+        Formula::Eq(
+            Box::new(Expr::Var("x".to_string())),
+            Box::new(Expr::Const(255))
+        )
+    );
+    let f = parse_formula("(= x 0b101)");
+    assert_eq!(
+        f,
+        Formula::Eq(
+            Box::new(Expr::Var("x".to_string())),
+            Box::new(Expr::Const(5))
+        )
+    );
+    let f = parse_formula("(= x -0x10)");
+    assert_eq!(
+        f,
+        Formula::Eq(
+            Box::new(Expr::Var("x".to_string())),
+            Box::new(Expr::Const(-16))
+        )
+    );
+}
+
+#[test]
+fn test_parse_subtraction_with_negative_literal() {
+    // the binary `-` needs a space, a `-` followed by digits is a literal
+    let f = parse_formula("(= (- x -2) 3)");
+    assert_eq!(
+        f,
+        Formula::Eq(
+            Box::new(Expr::Sub(
+                Box::new(Expr::Var("x".to_string())),
+                Box::new(Expr::Const(-2))
+            )),
+            Box::new(Expr::Const(3))
+        )
+    );
+    let closure = f.as_closure().unwrap();
+    assert!(closure(1));
+    assert!(!closure(3));
+}
+
+#[test]
+fn test_parse_literal_out_of_range() {
+    assert!(
+        FormulaParser::new()
+            .parse("(= x 99999999999999999999)")
+            .is_err()
+    );
+    assert!(
+        FormulaParser::new()
+            .parse("(= x 0xffffffffffffffffff)")
+            .is_err()
+    );
+}
+
+#[test]
+fn test_display_round_trip() {
+    for input in [
+        "(= x 1)",
+        "(and (= x 1) (or (>= (mod (+ t 1) 3) 0) (not (!= (* 2 t) -4))))",
+        "(forall y (exists z (< (- y z) 0x10)))",
+    ] {
+        let f = parse_formula(input);
+        assert_eq!(parse_formula(&f.to_string()), f);
+    }
+    assert_eq!(
+        parse_formula("(and (<= t 3) (> t -1))").to_string(),
+        "(and (<= t 3) (> t -1))"
+    );
+}
+
+#[test]
+fn test_parse_min_max() {
+    let f = parse_formula("(= (max x 3) 3)");
+    assert_eq!(
+        f,
+        Formula::Eq(
+            Box::new(Expr::Max(
+                Box::new(Expr::Var("x".to_string())),
+                Box::new(Expr::Const(3))
+            )),
+            Box::new(Expr::Const(3))
+        )
+    );
+    let closure = f.as_closure().unwrap();
+    assert!((0..=3).all(&closure));
+    assert!(!(4..10).any(&closure));
+
+    let f = parse_formula("(>= t (min 3 (max 5 t)))");
+    assert_eq!(f.to_string(), "(>= t (min 3 (max 5 t)))");
+}
+
+#[test]
+fn test_parse_ite() {
+    let f = parse_formula("(>= t (ite (< t 5) 3 8))");
+    assert_eq!(f.to_string(), "(>= t (ite (< t 5) 3 8))");
+    // available at 3 and 4 (offset 3), then from 8 on (offset 8)
+    let closure = f.as_closure().unwrap();
+    let available: Vec<usize> = (0..10).filter(|&t| closure(t)).collect();
+    assert_eq!(available, [3, 4, 8, 9]);
+}
+
+#[test]
+fn test_parse_every() {
+    let every = |m, r| {
+        Formula::Eq(
+            Box::new(Expr::Mod(Box::new(Expr::Var("t".to_string())), m)),
+            Box::new(Expr::Const(r)),
+        )
+    };
+    assert_eq!(parse_formula("(every 5 offset 2)"), every(5, 2));
+    assert_eq!(parse_formula("(every 3)"), every(3, 0));
+    assert_eq!(
+        parse_formula("(every 5 offset 2)"),
+        parse_formula("(= (mod t 5) 2)")
+    );
+
+    let closure = parse_formula("(every 5 offset 2)").as_closure().unwrap();
+    let available: Vec<usize> = (0..15).filter(|&t| closure(t)).collect();
+    assert_eq!(available, [2, 7, 12]);
+
+    assert!(FormulaParser::new().parse("(every 0)").is_err());
+    // an offset outside 0..m would never be matched
+    assert!(FormulaParser::new().parse("(every 3 offset 3)").is_err());
+    assert!(FormulaParser::new().parse("(every 3 offset -1)").is_err());
+}
+
+#[test]
+fn test_parse_xor() {
+    let f = parse_formula("(xor (= (mod t 2) 0) (= (mod t 3) 0))");
+    assert!(matches!(&f, Formula::Xor(fs) if fs.len() == 2));
+    assert_eq!(f.to_string(), "(xor (= (mod t 2) 0) (= (mod t 3) 0))");
+    let closure = f.as_closure().unwrap();
+    assert!(!closure(0) && closure(2) && closure(3) && !closure(6));
+}
+
+#[test]
+fn test_parse_n_ary_and_or() {
+    let eq = |n| {
+        Formula::Eq(
+            Box::new(Expr::Var("t".to_string())),
+            Box::new(Expr::Const(n)),
+        )
+    };
+    assert_eq!(
+        parse_formula("(and (= t 1) (= t 2) (= t 3))"),
+        Formula::And(vec![eq(1), eq(2), eq(3)])
+    );
+    assert_eq!(
+        parse_formula("(or (= t 1) (= t 2) (= t 3) (= t 4))"),
+        Formula::Or(vec![eq(1), eq(2), eq(3), eq(4)])
+    );
+    assert_eq!(parse_formula("(or (= t 1))"), Formula::Or(vec![eq(1)]));
+
+    // at least one argument is required
+    for input in ["(and)", "(or)", "(xor)"] {
+        assert!(FormulaParser::new().parse(input).is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn test_parse_true_false() {
+    assert_eq!(parse_formula("true"), Formula::True);
+    let f = parse_formula("(or (= t 1) false)");
+    assert_eq!(f.to_string(), "(or (= t 1) false)");
+    assert_eq!(parse_formula(&f.to_string()), f);
+}
+
+#[test]
+fn test_parse_forall_exists_in_range() {
+    let f = parse_formula("(forall y in 0 2 (>= t y))");
+    assert_eq!(
+        f,
+        Formula::ForallIn(
+            "y".to_string(),
+            0,
+            2,
+            Box::new(Formula::Ge(
+                Box::new(Expr::Var("t".to_string())),
+                Box::new(Expr::Var("y".to_string()))
+            ))
+        )
+    );
+    assert_eq!(f.to_string(), "(forall y in 0 2 (>= t y))");
+    let closure = f.as_closure().expect("closure failed");
+    assert_eq!(
+        (0..4).map(closure).collect::<Vec<_>>(),
+        [false, false, true, true]
+    );
+
+    let f = parse_formula("(exists y in -1 0x1 (= t y))");
+    assert!(matches!(f, Formula::ExistsIn(ref y, -1, 1, _) if y == "y"));
+    assert_eq!(parse_formula(&f.to_string()), f);
+
+    assert!(
+        FormulaParser::new()
+            .parse("(forall y in 0 (>= t y))")
+            .is_err()
+    );
+}
+
+#[test]
+fn test_parse_formula_errors() {
+    // a missing operand
+    let err = parser::parse_formula("(= x)").unwrap_err();
+    assert_eq!(err.position, Some(4));
+    assert_eq!(err.found.as_deref(), Some(")"));
+    assert_eq!(err.expected, ["integer", "variable", "`(`"]);
+    assert_eq!(
+        err.to_string(),
+        "unexpected `)` at position 4, expected one of integer, variable or `(`"
+    );
+
+    // a quantifier needs a variable
+    let err = parser::parse_formula("(forall 3 true)").unwrap_err();
+    assert_eq!(err.position, Some(8));
+    assert_eq!(err.expected, ["variable"]);
+
+    // unbalanced parentheses
+    let err = parser::parse_formula("(and (= t 1)").unwrap_err();
+    assert_eq!((err.position, err.found.as_deref()), (Some(12), None));
+    assert!(err.expected.contains(&"`)`".to_string()));
+    assert!(
+        err.to_string()
+            .starts_with("unexpected end of formula at position 12")
+    );
+    let err = parser::parse_formula("(< t 3) x").unwrap_err();
+    assert_eq!(err.expected, ["end of formula"]);
+
+    // an expression where a formula is expected
+    let err = parser::parse_formula("(mod t 2)").unwrap_err();
+    assert_eq!(err.found.as_deref(), Some("mod"));
+    assert!(err.expected.contains(&"`and`".to_string()));
+
+    // not a token at all
+    let err = parser::parse_formula("(= t $)").unwrap_err();
+    assert_eq!((err.position, err.found.as_deref()), (Some(5), Some("$")));
+
+    // not a syntax error
+    let err = parser::parse_formula("(every 0)").unwrap_err();
+    assert_eq!(err.position, None);
+    assert_eq!(err.to_string(), "the period of `every` must be positive");
+}
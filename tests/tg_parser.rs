@@ -0,0 +1,505 @@
+use lalrpop_util::ParseError;
+use ontime::formulae::{Expr, Formula};
+use ontime::parser::tg_parser::{LinesParser, NIDListParser};
+use ontime::parser::{ParsedLine, TIME_VAR_PLACEHOLDER, TemporalGraphParser};
+
+fn parse_ids(input: &str) -> Vec<String> {
+    NIDListParser::new().parse(input).expect("parse failed")
+}
+
+#[test]
+fn test_parse_id_list() {
+    assert_eq!(parse_ids("v0,v1,v2"), vec!["v0", "v1", "v2"]);
+}
+
+#[test]
+fn test_parse_spaced_id_list() {
+    assert_eq!(parse_ids("v0, v1 ,  v2"), vec!["v0", "v1", "v2"]);
+    assert_eq!(parse_ids(" v0 "), vec!["v0"]);
+}
+
+#[test]
+fn test_parse_id_range() {
+    assert_eq!(parse_ids("v0..v3"), vec!["v0", "v1", "v2", "v3"]);
+    assert_eq!(parse_ids("s_8..s_10"), vec!["s_8", "s_9", "s_10"]);
+    assert_eq!(parse_ids("v08..v10"), vec!["v08", "v09", "v10"]);
+}
+
+#[test]
+fn test_parse_mixed_id_list() {
+    assert_eq!(parse_ids("v0, v2..v4"), vec!["v0", "v2", "v3", "v4"]);
+    assert_eq!(parse_ids("a, v2 .. v3, b"), vec!["a", "v2", "v3", "b"]);
+}
+
+#[test]
+fn test_parse_invalid_id_range() {
+    // different prefixes
+    assert!(NIDListParser::new().parse("u0..v3").is_err());
+    // no numeric suffix
+    assert!(NIDListParser::new().parse("s..t").is_err());
+    // empty range
+    assert!(NIDListParser::new().parse("v3..v0").is_err());
+}
+
+const TWO_STATE_T: &str = "
+node s0: owner[0]
+node s1: owner[0]
+edge s0 -> s0
+edge s1 -> s1
+edge s0 -> s1: (>= t 5)
+";
+
+const TWO_STATE_X: &str = "
+node s0: owner[0]
+node s1: owner[0]
+edge s0 -> s0
+edge s1 -> s1
+edge s0 -> s1: (>= x 5)
+";
+
+#[test]
+fn test_parse_default_time_var() {
+    let parser = TemporalGraphParser::new();
+    assert_eq!(parser.time_var, "t");
+
+    let graph = parser.parse(TWO_STATE_T).expect("parse failed");
+    assert_eq!(graph.successors_at(0, 4).collect::<Vec<_>>(), vec![0]);
+    let mut successors: Vec<_> = graph.successors_at(0, 5).collect();
+    successors.sort();
+    assert_eq!(successors, vec![0, 1]);
+}
+
+#[test]
+fn test_parse_configured_time_var() {
+    let graph = TemporalGraphParser::with_time_var("x")
+        .parse(TWO_STATE_X)
+        .expect("parse failed");
+    assert_eq!(graph.successors_at(0, 4).collect::<Vec<_>>(), vec![0]);
+    let mut successors: Vec<_> = graph.successors_at(0, 5).collect();
+    successors.sort();
+    assert_eq!(successors, vec![0, 1]);
+}
+
+#[test]
+fn test_parse_sugar_with_configured_time_var() {
+    let graph = TemporalGraphParser::with_time_var("x")
+        .parse(
+            "node s0
+             node s1
+             node s2
+             node s3
+             edge s0 -> s1: (and (every 2) (>= x 3))
+             edge s1 -> s2: (1,4)
+             edge s2 -> s3 @[2,3]",
+        )
+        .expect("parse failed");
+    let available = |from| -> Vec<usize> {
+        (0..8)
+            .filter(|&t| graph.successors_at(from, t).next().is_some())
+            .collect()
+    };
+    assert_eq!(available(0), [4, 6]);
+    assert_eq!(available(1), [1, 4]);
+    assert_eq!(available(2), [2, 3]);
+}
+
+#[test]
+fn test_parse_time_var_mismatch() {
+    for (parser, input) in [
+        (TemporalGraphParser::new(), TWO_STATE_X),
+        (TemporalGraphParser::with_time_var("x"), TWO_STATE_T),
+    ] {
+        match parser.parse(input) {
+            Err(ParseError::User { error }) => {
+                assert!(error.contains("s0 -> s1"), "error: {}", error);
+                assert!(error.contains("time variable"), "error: {}", error);
+            }
+            other => panic!(
+                "expected a time variable error, got {:?}",
+                other.map(|_| ())
+            ),
+        }
+    }
+}
+
+fn parse_edge_formula(input: &str) -> Formula {
+    match LinesParser::new().parse(input).expect("parse failed").pop() {
+        Some(ParsedLine::Edge(_, _, Some(f), _)) => f,
+        other => panic!("expected an edge with a formula, got {:?}", other),
+    }
+}
+
+// parsed lines keep the placeholder time variable of sugar
+fn t() -> Box<Expr> {
+    Box::new(Expr::Var(TIME_VAR_PLACEHOLDER.to_string()))
+}
+
+#[test]
+fn test_parse_interval_sugar() {
+    assert_eq!(
+        parse_edge_formula("edge v0 -> v1 @[3,7]"),
+        Formula::And(vec![
+            Formula::Ge(t(), Box::new(Expr::Const(3))),
+            Formula::Le(t(), Box::new(Expr::Const(7))),
+        ])
+    );
+    assert_eq!(
+        parse_edge_formula("edge v0 -> v1 @[3,]"),
+        Formula::Ge(t(), Box::new(Expr::Const(3)))
+    );
+    assert_eq!(
+        parse_edge_formula("edge v0 -> v1 @[,7]"),
+        Formula::Le(t(), Box::new(Expr::Const(7)))
+    );
+    assert_eq!(parse_edge_formula("edge v0 -> v1 @[ , ]"), Formula::True);
+}
+
+#[test]
+fn test_interval_sugar_availability() {
+    let graph = TemporalGraphParser::new()
+        .parse(
+            "
+            node v0
+            node v1
+            node v2
+            node v3
+            edge v0 -> v1 @[3,7]
+            edge v1 -> v2 @[3,]
+            edge v2 -> v3 @[,7]
+            ",
+        )
+        .expect("parse failed");
+    for time in 0..12 {
+        let available = |from| graph.successors_at(from, time).next().is_some();
+        assert_eq!(available(0), (3..=7).contains(&time), "time {}", time);
+        assert_eq!(available(1), time >= 3, "time {}", time);
+        assert_eq!(available(2), time <= 7, "time {}", time);
+    }
+}
+
+#[test]
+fn test_integer_literal_out_of_range() {
+    for input in [
+        "node a\nnode b\nedge a -> b @[0, 99999999999999999999]",
+        "node a\nedge a -> a [w=-99999999999999999999]",
+        "#define K 99999999999999999999",
+    ] {
+        match TemporalGraphParser::new().parse(input) {
+            Err(ParseError::User { error }) => assert!(error.contains("out of range"), "{}", error),
+            other => panic!(
+                "expected an out of range error for {:?}, got {:?}",
+                input,
+                other.map(|_| ())
+            ),
+        }
+    }
+}
+
+#[test]
+fn test_edge_weight() {
+    let graph = TemporalGraphParser::new()
+        .parse("node a\nnode b\nedge a -> b: (>= t 1) [w=-2]\nedge b -> a [w=3]\nedge a -> a @[0,2] [w=0]")
+        .unwrap();
+    let weight = |from: &str| {
+        let mut weights: Vec<i64> = graph
+            .edges_from(graph.node_id_map[from])
+            .map(|e| e.weight())
+            .collect();
+        weights.sort();
+        weights
+    };
+    assert_eq!(weight("a"), [-2, 0]);
+    assert_eq!(weight("b"), [3]);
+
+    assert!(
+        TemporalGraphParser::new()
+            .parse("node a\nedge a -> a [x=1]")
+            .is_err()
+    );
+}
+
+#[test]
+fn test_parse_owner_values() {
+    let owner_of = |attr: &str| {
+        let graph = TemporalGraphParser::new()
+            .parse(&format!("node s0: {}", attr))
+            .expect("parse failed");
+        graph.node_ownership()[0]
+    };
+    for attr in ["owner[0]", "owner=0", "owner=true"] {
+        assert!(owner_of(attr), "{}", attr);
+    }
+    for attr in ["owner[1]", "owner=1", "owner=false"] {
+        assert!(!owner_of(attr), "{}", attr);
+    }
+}
+
+#[test]
+fn test_parse_invalid_owner() {
+    for attr in ["owner[2]", "owner[-1]", "owner=yes", "owner=True"] {
+        match TemporalGraphParser::new().parse(&format!("node s0: {}", attr)) {
+            Err(ParseError::User { error }) => {
+                assert!(error.contains("invalid owner"), "error: {}", error)
+            }
+            other => panic!(
+                "expected an owner error for {}, got {:?}",
+                attr,
+                other.map(|_| ())
+            ),
+        }
+    }
+}
+
+#[test]
+fn test_parse_define() {
+    let graph = TemporalGraphParser::new()
+        .parse(
+            "
+            #define K 5
+            node s0: owner[0]
+            node s1: owner[0]
+            edge s0 -> s1: (and (>= t K) (< t (+ K Len)))
+            #define Len 2
+            ",
+        )
+        .expect("parse failed");
+    let available: Vec<usize> = (0..10)
+        .filter(|&t| graph.successors_at(0, t).next().is_some())
+        .collect();
+    assert_eq!(available, [5, 6]);
+}
+
+#[test]
+fn test_parse_invalid_define() {
+    for (input, symbol) in [
+        ("node s0\nedge s0 -> s0: (>= t K)", "'K'"),
+        ("#define K 1\n#define K 2", "K"),
+        ("#define t 1", "t"),
+    ] {
+        match TemporalGraphParser::new().parse(input) {
+            Err(ParseError::User { error }) => {
+                assert!(error.contains(symbol), "error: {}", error)
+            }
+            other => panic!(
+                "expected an error for {:?}, got {:?}",
+                input,
+                other.map(|_| ())
+            ),
+        }
+    }
+}
+
+#[test]
+fn test_parse_reader() {
+    let input = format!(
+        "/* a block comment
+            spanning lines */
+        {}
+        // a line comment
+        edge s1 -> s0: (= t 3) /* trailing */
+        edge s0 -> s0:
+            (>= t 3)",
+        include_str!("fixtures/two_state.tg")
+    );
+    let parser = TemporalGraphParser::new();
+    let expected = parser.parse(&input).expect("parse failed");
+    let graph = parser
+        .parse_reader(std::io::Cursor::new(&input))
+        .expect("parse_reader failed");
+
+    assert_eq!(graph.node_id_map, expected.node_id_map);
+    assert_eq!(graph.node_ownership(), expected.node_ownership());
+    assert_eq!(graph.edge_count(), 5);
+    for n in graph.nodes() {
+        for t in 0..8 {
+            let mut successors: Vec<_> = graph.successors_at(n, t).collect();
+            let mut expected_successors: Vec<_> = expected.successors_at(n, t).collect();
+            successors.sort();
+            expected_successors.sort();
+            assert_eq!(successors, expected_successors);
+        }
+    }
+}
+
+#[test]
+fn test_parse_reader_errors() {
+    let parser = TemporalGraphParser::new();
+    let error = parser
+        .parse_reader(std::io::Cursor::new("node s0\nnode s1\nedge s0 => s1\n"))
+        .expect_err("syntax error expected");
+    assert!(error.starts_with("line 3: "), "error: {}", error);
+
+    let error = parser
+        .parse_reader(std::io::Cursor::new("node s0\nedge s0 -> s1\n"))
+        .expect_err("undeclared node expected");
+    assert!(error.contains("s1"), "error: {}", error);
+}
+
+#[test]
+fn test_to_tg_round_trip() {
+    let input = "
+        #define K 4
+        node s0: owner[0], label[\"Start\"]
+        node s1: owner=false
+        node s2
+        edge s0 -> s0
+        edge s0 -> s1: (and (>= t K) (or (= (mod t 2) 0) false)) [w=-3]
+        edge s1 -> s2 @[2,]
+        edge s2 -> s0: (xor (= t 1) (= t 2))
+    ";
+    let edges = |graph: &ontime::temporal_graphs::TemporalGraph| {
+        let mut edges: Vec<_> = graph
+            .edges()
+            .map(|e| (*e.source(), *e.target(), e.formula().clone(), e.weight()))
+            .collect();
+        edges.sort_by_key(|(s, t, f, w)| (*s, *t, f.to_string(), *w));
+        edges
+    };
+
+    let parser = TemporalGraphParser::new();
+    let graph = parser.parse(input).expect("parse failed");
+    let tg = graph.to_tg();
+    let reparsed = parser.parse(&tg).expect("re-parse failed");
+
+    assert_eq!(reparsed.node_id_map, graph.node_id_map);
+    assert_eq!(reparsed.node_attrs, graph.node_attrs);
+    assert_eq!(edges(&reparsed), edges(&graph));
+    assert_eq!(reparsed.to_tg(), tg);
+    assert!(tg.starts_with("node s0: owner[0], label[\"Start\"]\nnode s1: owner[1]\nnode s2\n"));
+    assert!(tg.contains("edge s0 -> s0\n"));
+    assert!(tg.contains("[w=-3]\n"));
+}
+
+#[test]
+fn test_quoted_labels() {
+    let input = r#"
+        node s0: label["start state"]
+        node s1: label["say \"hi\""], owner[1]
+        node s2: label["back\\slash \d"]
+    "#;
+    let parser = TemporalGraphParser::new();
+    let graph = parser.parse(input).expect("parse failed");
+    assert_eq!(graph.label(0), Some("start state"));
+    assert_eq!(graph.label(1), Some(r#"say "hi""#));
+    // a backslash before another character is kept
+    assert_eq!(graph.label(2), Some(r"back\slash \d"));
+
+    let tg = graph.to_tg();
+    assert!(
+        tg.contains(r#"node s1: owner[1], label["say \"hi\""]"#),
+        "{}",
+        tg
+    );
+    assert!(
+        tg.contains(r#"node s2: label["back\\slash \\d"]"#),
+        "{}",
+        tg
+    );
+    let reparsed = parser.parse(&tg).expect("re-parse failed");
+    assert_eq!(reparsed.node_attrs, graph.node_attrs);
+}
+
+#[test]
+fn test_time_variable_on_both_sides() {
+    let graph = TemporalGraphParser::new()
+        .parse("node s0\nnode s1\nedge s0 -> s1: (>= (* 2 t) (+ t 5))")
+        .expect("parse failed");
+    let available: Vec<usize> = (0..10)
+        .filter(|&t| graph.successors_at(0, t).next().is_some())
+        .collect();
+    assert_eq!(available, [5, 6, 7, 8, 9]);
+}
+
+#[test]
+fn test_constant_constraints() {
+    let graph = TemporalGraphParser::new()
+        .parse("node s0\nnode s1\nedge s0 -> s1: (>= 5 3)\nedge s1 -> s0: (>= 3 5)")
+        .expect("parse failed");
+    let s0_s1 = graph.edges_from(0).next().unwrap();
+    assert_eq!(s0_s1.formula(), &Formula::True);
+    assert!((0..100).all(|t| s0_s1.is_available(t)));
+    let s1_s0 = graph.edges_from(1).next().unwrap();
+    assert_eq!(s1_s0.formula(), &Formula::False);
+    assert!((0..100).all(|t| !s1_s0.is_available(t)));
+}
+
+#[test]
+fn test_parse_with_directives() {
+    let input = "
+        // time_bound: 6
+        /* a block comment
+           // targets: s0
+        */
+
+        node s0 // the start
+        //targets:  s1
+
+        // plain comment
+        node s1: owner[0] /* inline */
+        edge s0 -> s1: (>= t 2) // late edge
+    ";
+    let (graph, directives) = TemporalGraphParser::new()
+        .parse_with_directives(input)
+        .expect("parse failed");
+    assert_eq!(graph.node_count, 2);
+    assert_eq!(
+        directives,
+        [
+            ("time_bound".to_string(), "6".to_string()),
+            ("targets".to_string(), "s1".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_malformed_formula() {
+    match TemporalGraphParser::new().parse("node s0\nedge s0 -> s0: (>= t)") {
+        Err(ParseError::User { error }) => assert_eq!(
+            error,
+            "invalid formula (>= t): unexpected `)` at position 5, \
+             expected one of integer, variable or `(`"
+        ),
+        other => panic!("expected a formula error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_parse_duplicate_node() {
+    match TemporalGraphParser::new().parse("node s0: owner[0]\nnode s1\nnode s0: owner[1]") {
+        Err(ParseError::User { error }) => {
+            assert_eq!(error, "node(s) declared more than once: s0")
+        }
+        other => panic!(
+            "expected a duplicate node error, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
+
+#[test]
+fn test_canonical_hash() {
+    let hash = |input: &str| {
+        TemporalGraphParser::new()
+            .parse(input)
+            .expect("parse failed")
+            .canonical_hash()
+    };
+    let original = hash(TWO_STATE_T);
+    let reordered = hash(
+        "node s1: owner[0]
+        edge s0 -> s1: (and (>= t 5) true)
+        edge s1 -> s1
+        node s0: owner[0]
+        edge s0 -> s0",
+    );
+    assert_eq!(original, reordered);
+
+    // a different constraint, owner or label
+    for different in [
+        "node s0: owner[0]\nnode s1: owner[0]\nedge s0 -> s0\nedge s1 -> s1\nedge s0 -> s1: (>= t 6)",
+        "node s0: owner[1]\nnode s1: owner[0]\nedge s0 -> s0\nedge s1 -> s1\nedge s0 -> s1: (>= t 5)",
+        "node s0: owner[0], label[\"a\"]\nnode s1: owner[0]\nedge s0 -> s0\nedge s1 -> s1\nedge s0 -> s1: (>= t 5)",
+    ] {
+        assert_ne!(hash(different), original, "{}", different);
+    }
+}